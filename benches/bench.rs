@@ -28,7 +28,6 @@ fn char(b: &mut Bencher) {
     });
 }
 
-/// Heap allocated (slow)
 #[bench]
 fn str(b: &mut Bencher) {
     b.iter(|| {
@@ -43,7 +42,6 @@ fn bstr(b: &mut Bencher) {
     });
 }
 
-/// Heap allocated (slow)
 #[bench]
 fn cstr(b: &mut Bencher) {
     b.iter(|| {
@@ -69,7 +67,6 @@ fn vec_char(b: &mut Bencher) {
     });
 }
 
-/// Heap allocated (slow)
 #[bench]
 fn vec_str(b: &mut Bencher) {
     b.iter(|| {
@@ -84,7 +81,6 @@ fn vec_bstr(b: &mut Bencher) {
     });
 }
 
-/// Heap allocated (slow)
 #[bench]
 fn vec_cstr(b: &mut Bencher) {
     b.iter(|| {
@@ -110,7 +106,6 @@ fn tuple_char(b: &mut Bencher) {
     });
 }
 
-/// Heap allocated (slow)
 #[bench]
 fn tuple_str(b: &mut Bencher) {
     b.iter(|| {
@@ -125,10 +120,44 @@ fn tuple_bstr(b: &mut Bencher) {
     });
 }
 
-/// Heap allocated (slow)
 #[bench]
 fn tuple_cstr(b: &mut Bencher) {
     b.iter(|| {
         let _ = boo!((c"1", c"2", c"3", c"4", c"5", c"6", c"7", c"8", c"9"));
     });
 }
+
+////////////////////////////////////////////////////////////////////////////////
+// Large payloads
+////////////////////////////////////////////////////////////////////////////////
+
+#[bench]
+fn include_bytes_small(b: &mut Bencher) {
+    let mut buf = [0; 26];
+    b.iter(|| {
+        boo_include_bytes!("../assets/lorem_ipsum.txt").decrypt_into(&mut buf);
+    });
+}
+
+#[bench]
+fn include_bytes_large(b: &mut Bencher) {
+    let mut buf = alloc::vec![0; include_bytes!("../assets/boo.png").len()];
+    b.iter(|| {
+        boo_include_bytes!("../assets/boo.png").decrypt_into(&mut buf);
+    });
+}
+
+#[bench]
+fn str_large(b: &mut Bencher) {
+    b.iter(|| {
+        let _ = boo!("Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur.");
+    });
+}
+
+#[bench]
+fn stream_large_into_buffer(b: &mut Bencher) {
+    let mut buf = alloc::vec![0; include_bytes!("../assets/boo.png").len()];
+    b.iter(|| {
+        boo_stream!("../assets/boo.png").read(&mut buf);
+    });
+}
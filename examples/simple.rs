@@ -3,7 +3,6 @@
 //! Demonstrates secure string handling with compile-time encryption
 //! and runtime decryption.
 
-extern crate alloc;
 #[macro_use]
 extern crate boo;
 
@@ -23,13 +22,13 @@ boo_init!();
 /// ```
 /// let key = api_key();  // Decrypted in memory
 /// authenticate(&key);   // Used while decrypted
-/// drop(key);            // Memory is secured after dropping
+/// drop(key);            // Memory is wiped when dropped
 /// ```
 ///
 /// # Note
 ///
 /// Each call decrypts the string, no persistent decrypted state.
-fn api_key() -> String {
+fn api_key() -> impl core::ops::Deref<Target = str> {
     boo!("secret-api-key")
 }
 
@@ -40,7 +39,7 @@ fn main() {
     {
         let key = api_key(); // Decrypted here
         authenticate(&key);
-    } // Secured here
+    } // Wiped here
 
     // Example 2: Immediate usage
     // "secret-api-key" decrypted and secured within method call
@@ -49,13 +48,21 @@ fn main() {
     println!("Api key: {}", boo!("secret-api-key"));
 
     // Example 3: Secure comparison
-    // "password" decrypted only for comparison
-    if "password" == boo!("password") {
+    // "password" never decrypted as a whole, compared in constant time
+    if boo_eq!("password", "password") {
         println!("Access granted");
     }
 
-    // Example 4: Unsafe pattern (avoid)
+    // Example 4: Closure-scoped usage
+    // "secret-api-key" decrypted only while the closure runs, then wiped
+    boo_with!("secret-api-key", |key: &str| authenticate(key));
+
+    // Example 5: Unsafe pattern (avoid)
     let secret = boo!("top-secret"); // Remains decrypted until drop
     // ... rest of the program ...
     drop(secret); // Explicit cleanup recommended
+
+    // Example 6: Copies escape the wipe (avoid)
+    let copy = boo!("top-secret").to_owned(); // Heap copy, never wiped
+    drop(copy);
 }
@@ -21,6 +21,7 @@ fn main() {
     let root_password = boo!("ChangeThis!");
     let byte_str = boo!(b"raw\x00bytes");
     let c_str = boo!(c"null terminated");
+    let wide_str = boo_wide!("UTF-16 null terminated");
 
     // Character types
     let null_char = boo!('\0');
@@ -40,7 +41,7 @@ fn main() {
     let complex_structure = boo!((
         "header",
         [0u8, 1, 2],
-        [("item1", 3.14), ("item2", 2.718)],
+        [("item1", 3.5), ("item2", 2.75)],
         c"end marker"
     ));
 }
@@ -0,0 +1,163 @@
+//! # Warning
+//!
+//! This module is emitted as is by `boo_init!()` into the calling crate.
+//! It must only use `core`, as the calling crate may be `no_std`.
+//!
+//! Large encrypted files, decrypted into a caller buffer instead of the stack.
+
+/// Large encrypted byte string, decrypted into a caller buffer
+///
+/// The ciphertext stays in a static, so that the plaintext is only ever written to the caller buffer.
+/// Compressed ciphertexts are decrypted piece by piece by a [`BooStream`] while being decompressed.
+#[derive(Clone, Copy)]
+pub struct BooBlob {
+    ciphertext: &'static [u8],
+    shares: &'static SubkeyShares,
+    nonce: [u8; 12],
+    /// Length of the plaintext
+    len: usize,
+    /// Whether the plaintext is LZ4 compressed before being encrypted
+    compressed: bool,
+}
+
+impl BooBlob {
+    /// Creates a blob over `ciphertext`, encrypted with the subkey of `shares` and `nonce`
+    #[inline(always)]
+    pub const fn new(
+        ciphertext: &'static [u8],
+        shares: &'static SubkeyShares,
+        nonce: [u8; 12],
+    ) -> Self {
+        Self {
+            ciphertext,
+            shares,
+            nonce,
+            len: ciphertext.len(),
+            compressed: false,
+        }
+    }
+
+    /// Creates a blob over `ciphertext`, compressed with LZ4 from `len` bytes before being encrypted
+    #[inline(always)]
+    pub const fn compressed(
+        ciphertext: &'static [u8],
+        shares: &'static SubkeyShares,
+        nonce: [u8; 12],
+        len: usize,
+    ) -> Self {
+        Self {
+            ciphertext,
+            shares,
+            nonce,
+            len,
+            compressed: true,
+        }
+    }
+
+    /// Gets the number of decrypted bytes
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if there are no bytes at all
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Decrypts the bytes into the start of `buffer`
+    ///
+    /// # Returns
+    ///
+    /// The decrypted bytes, borrowed from `buffer`
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` is shorter than [`BooBlob::len`]
+    pub fn decrypt_into<'a>(&self, buffer: &'a mut [u8]) -> &'a mut [u8] {
+        let buffer = &mut buffer[..self.len];
+        if self.compressed {
+            lz4_decompress(
+                BooStream::new(self.ciphertext, self.shares, self.nonce),
+                buffer,
+            );
+            return buffer;
+        }
+
+        buffer.copy_from_slice(self.ciphertext);
+        let mut subkey = subkey(self.shares);
+        decrypt_at(buffer, &subkey, &self.nonce, 0);
+        wipe(&mut subkey);
+
+        buffer
+    }
+}
+
+impl ::core::fmt::Debug for BooBlob {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_struct("BooBlob")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Large encrypted string, decrypted into a caller buffer
+#[derive(Clone, Copy)]
+pub struct BooStrBlob {
+    bytes: BooBlob,
+}
+
+impl BooStrBlob {
+    /// Creates a blob over the encrypted bytes of a UTF-8 string
+    ///
+    /// # Safety
+    ///
+    /// The decrypted bytes must be valid UTF-8.
+    #[inline(always)]
+    pub const unsafe fn new(bytes: BooBlob) -> Self {
+        Self { bytes }
+    }
+
+    /// Gets the length of the decrypted string in bytes
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Checks if the string is empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Decrypts the string into the start of `buffer`
+    ///
+    /// # Returns
+    ///
+    /// The decrypted string, borrowed from `buffer`
+    ///
+    /// # Panics
+    ///
+    /// If `buffer` is shorter than [`BooStrBlob::len`]
+    pub fn decrypt_into<'a>(&self, buffer: &'a mut [u8]) -> &'a mut str {
+        let bytes = self.bytes.decrypt_into(buffer);
+
+        // Safety: checked by the caller of `new`
+        unsafe { ::core::str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    /// Gets the encrypted bytes of the string
+    #[inline(always)]
+    pub const fn as_bytes(&self) -> BooBlob {
+        self.bytes
+    }
+}
+
+impl ::core::fmt::Debug for BooStrBlob {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_struct("BooStrBlob")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,125 @@
+//! # Warning
+//!
+//! This module is imported as is in the proc macro when the `chacha20` feature is enabled.
+//! Like `utils.rs`, it must not depend on anything outside `core`.
+//!
+//! ChaCha20 stream cipher as described in [RFC 8439](https://www.rfc-editor.org/rfc/rfc8439).
+
+/// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+/// Size of a ChaCha20 key in bytes.
+#[allow(unused)]
+pub const KEY_LEN: usize = 32;
+
+/// Size of a ChaCha20 nonce in bytes.
+#[allow(unused)]
+pub const NONCE_LEN: usize = 12;
+
+/// Size of a ChaCha20 keystream block in bytes.
+pub const BLOCK_LEN: usize = 64;
+
+/// Reads a little endian `u32` from `bytes` at `offset`.
+#[inline(always)]
+const fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Applies the ChaCha quarter round on the `a`, `b`, `c` and `d` words of `state`.
+#[inline(always)]
+const fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// Computes the 64 bytes keystream block number `counter`.
+pub const fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut initial = [0; 16];
+    let mut i = 0;
+    while i < 4 {
+        initial[i] = CONSTANTS[i];
+        i += 1;
+    }
+    let mut i = 0;
+    while i < 8 {
+        initial[4 + i] = read_u32(key, i * 4);
+        i += 1;
+    }
+    initial[12] = counter;
+    let mut i = 0;
+    while i < 3 {
+        initial[13 + i] = read_u32(nonce, i * 4);
+        i += 1;
+    }
+
+    let mut state = initial;
+    let mut round = 0;
+    while round < 10 {
+        // Column rounds
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        // Diagonal rounds
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+        round += 1;
+    }
+
+    let mut block = [0; 64];
+    let mut i = 0;
+    while i < 16 {
+        let word = state[i].wrapping_add(initial[i]).to_le_bytes();
+        block[i * 4] = word[0];
+        block[i * 4 + 1] = word[1];
+        block[i * 4 + 2] = word[2];
+        block[i * 4 + 3] = word[3];
+        i += 1;
+    }
+
+    block
+}
+
+/// Applies XOR between each byte of `source` and the ChaCha20 keystream, starting at block `0`.
+///
+/// Encryption and decryption are the same operation.
+pub const fn chacha20(source: &mut [u8], key: &[u8; 32], nonce: &[u8; 12]) {
+    chacha20_at(source, key, nonce, 0);
+}
+
+/// Applies XOR between each byte of `source` and the ChaCha20 keystream, starting at byte `offset`.
+pub const fn chacha20_at(source: &mut [u8], key: &[u8; 32], nonce: &[u8; 12], offset: usize) {
+    let mut i = 0;
+    while i < source.len() {
+        let position = offset + i;
+        let block = chacha20_block(key, (position / BLOCK_LEN) as u32, nonce);
+
+        let mut j = position % BLOCK_LEN;
+        while j < BLOCK_LEN && i < source.len() {
+            source[i] ^= block[j];
+            i += 1;
+            j += 1;
+        }
+    }
+}
+
+/// Gets the byte at `index` of the ChaCha20 keystream, starting at block `0`.
+///
+/// The whole block is computed for each byte, trading speed for never holding more than one block.
+#[allow(unused)]
+pub const fn chacha20_keystream(key: &[u8; 32], nonce: &[u8; 12], index: usize) -> u8 {
+    chacha20_block(key, (index / BLOCK_LEN) as u32, nonce)[index % BLOCK_LEN]
+}
@@ -0,0 +1,214 @@
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote};
+
+use crate::chacha20::{KEY_LEN, NONCE_LEN, chacha20, chacha20_block};
+use crate::key;
+use crate::random::Random;
+use crate::utils::{xor, xor_nonce};
+
+/// Number of shares each literal subkey is split into
+const KEY_SHARES: usize = 4;
+
+/// Length of the repeating-key XOR subkeys
+const XOR_SUBKEY_LEN: usize = 64;
+
+/// Cipher used to encrypt literals, selected with cargo features
+///
+/// The choice can't be made by `boo_init!()` because every `boo!()` expansion
+/// must encrypt with the same cipher than the one decrypting at runtime.
+pub enum Cipher {
+    /// Repeating-key XOR (default)
+    Xor,
+    /// ChaCha20 stream cipher (`chacha20` feature)
+    ChaCha20,
+}
+
+impl Cipher {
+    /// Cipher selected for this build
+    pub const SELECTED: Self = if cfg!(feature = "chacha20") {
+        Self::ChaCha20
+    } else {
+        Self::Xor
+    };
+
+    /// Gets the master key deriving the literal subkeys
+    ///
+    /// The key of any length is compressed into a ChaCha20 key, derived from [`key()`].
+    pub fn key() -> [u8; KEY_LEN] {
+        derive_chacha20_key(key())
+    }
+
+    /// Gets the length of the literal subkeys
+    ///
+    /// A longer subkey makes the repeating-key XOR keystream period longer.
+    fn subkey_len(&self) -> usize {
+        match self {
+            Self::Xor => XOR_SUBKEY_LEN,
+            Self::ChaCha20 => KEY_LEN,
+        }
+    }
+
+    /// Derives the subkey of a single literal from [`Cipher::key`] and `salt`
+    ///
+    /// The subkey is the ChaCha20 keystream of the master key with `salt` as nonce,
+    /// so that a subkey found in memory can't be reversed into the master key, even knowing `salt`.
+    pub fn subkey(&self, salt: &[u8; NONCE_LEN]) -> Vec<u8> {
+        let mut subkey = vec![0; self.subkey_len()];
+        chacha20(&mut subkey, &Self::key(), salt);
+
+        subkey
+    }
+
+    /// Encrypts `bytes` in place with the subkey derived from `salt`, mixing `nonce` into the keystream
+    pub fn encrypt(&self, bytes: &mut [u8], salt: &[u8; NONCE_LEN], nonce: &[u8; NONCE_LEN]) {
+        self.apply(bytes, &self.subkey(salt), nonce);
+    }
+
+    /// Applies the keystream of `key` and `nonce` on `bytes`
+    fn apply(&self, bytes: &mut [u8], key: &[u8], nonce: &[u8; NONCE_LEN]) {
+        match self {
+            Self::Xor => xor_nonce(bytes, key, nonce),
+            Self::ChaCha20 => chacha20(bytes, key.try_into().unwrap(), nonce),
+        }
+    }
+
+    /// Gets the name of the runtime function equivalent to [`Cipher::apply`]
+    fn runtime_apply(&self) -> Ident {
+        match self {
+            Self::Xor => format_ident!("xor_nonce"),
+            Self::ChaCha20 => format_ident!("chacha20"),
+        }
+    }
+
+    /// Gets the name of the runtime function equivalent to [`Cipher::apply`], starting at an offset
+    fn runtime_apply_at(&self) -> Ident {
+        match self {
+            Self::Xor => format_ident!("xor_nonce_at"),
+            Self::ChaCha20 => format_ident!("chacha20_at"),
+        }
+    }
+
+    /// Gets the name of the runtime function computing a single byte of the [`Cipher::apply`] keystream
+    fn runtime_keystream(&self) -> Ident {
+        match self {
+            Self::Xor => format_ident!("xor_nonce_keystream"),
+            Self::ChaCha20 => format_ident!("chacha20_keystream"),
+        }
+    }
+
+    /// Generates the subkey definitions and the decryption functions emitted in the `__boo` module
+    ///
+    /// The master key is never emitted, each literal embedding its own subkey shares.
+    pub fn runtime(&self) -> TokenStream {
+        let len = self.subkey_len();
+        let apply_at = self.runtime_apply_at();
+        let keystream = self.runtime_keystream();
+
+        quote! {
+            /// Length of the literal subkeys
+            pub const SUBKEY_LEN: usize = #len;
+
+            /// Random shares of a literal subkey, XORed together by [`subkey`]
+            pub type SubkeyShares = [[u8; SUBKEY_LEN]; #KEY_SHARES];
+
+            /// Decrypts in place `bytes`, found at `offset` in a literal encrypted with `subkey` and `nonce`
+            #[inline(always)]
+            pub fn decrypt_at(bytes: &mut [u8], subkey: &[u8; SUBKEY_LEN], nonce: &[u8; #NONCE_LEN], offset: usize) {
+                #apply_at(bytes, subkey, nonce, offset);
+            }
+
+            /// Compares `input` with `ciphertext`, encrypted with the subkey of `shares` and `nonce`, in constant time
+            pub fn decrypt_eq(ciphertext: &[u8], input: &[u8], shares: &'static SubkeyShares, nonce: &[u8; #NONCE_LEN]) -> bool {
+                let mut subkey = subkey(shares);
+                let eq = ct_eq(ciphertext, input, |i| #keystream(&subkey, nonce, i));
+                wipe(&mut subkey);
+
+                eq
+            }
+        }
+    }
+
+    /// Generates the expression referencing the shares of the subkey derived from `salt`
+    ///
+    /// The subkey is split into [`KEY_SHARES`] random shares XORed together, stored in a static.
+    /// It only exists once recombined by the runtime `subkey()` function.
+    pub fn subkey_shares(&self, salt: &[u8; NONCE_LEN], random: &mut Random) -> TokenStream {
+        let mut last = self.subkey(salt);
+        let mut shares = (1..KEY_SHARES)
+            .map(|_| {
+                let mut share = vec![0; last.len()];
+                random.fill(&mut share);
+                xor(&mut last, &share);
+
+                share
+            })
+            .collect::<Vec<_>>();
+        shares.push(last);
+        let shares = shares.iter().map(|share| Literal::byte_string(share));
+
+        quote! {{
+            static SHARES: crate::__boo::SubkeyShares = [#(*#shares),*];
+            &SHARES
+        }}
+    }
+
+    /// Generates the statements decrypting in place a `bytes: [u8; N]` binding
+    ///
+    /// The subkey shares and the `nonce` used for encryption are embedded next to the ciphertext.
+    /// The subkey is wiped once used.
+    pub fn decrypt(
+        &self,
+        salt: &[u8; NONCE_LEN],
+        nonce: &[u8; NONCE_LEN],
+        random: &mut Random,
+    ) -> TokenStream {
+        let shares = self.subkey_shares(salt, random);
+        let nonce = Literal::byte_string(nonce);
+        let apply = self.runtime_apply();
+
+        quote! {
+            let mut subkey = crate::__boo::subkey(#shares);
+            crate::__boo::#apply(&mut bytes, &subkey, #nonce);
+            crate::__boo::wipe(&mut subkey);
+        }
+    }
+
+    /// Generates the expression comparing an `input: &[u8]` binding with `ciphertext` in constant time
+    ///
+    /// The ciphertext is decrypted one keystream byte at a time, never as a whole.
+    pub fn compare(
+        &self,
+        ciphertext: &[u8],
+        salt: &[u8; NONCE_LEN],
+        nonce: &[u8; NONCE_LEN],
+        random: &mut Random,
+    ) -> TokenStream {
+        let ciphertext = Literal::byte_string(ciphertext);
+        let shares = self.subkey_shares(salt, random);
+        let nonce = Literal::byte_string(nonce);
+
+        quote! {
+            crate::__boo::decrypt_eq(#ciphertext, input, #shares, #nonce)
+        }
+    }
+}
+
+/// Compresses a key of any length into a ChaCha20 key
+///
+/// Each 32 bytes chunk of `key` is mixed into the previous state through the ChaCha20 block function,
+/// using the chunk index as counter and the key length as nonce.
+pub fn derive_chacha20_key(key: &[u8]) -> [u8; KEY_LEN] {
+    let mut nonce = [0; NONCE_LEN];
+    nonce[..8].copy_from_slice(&(key.len() as u64).to_le_bytes());
+
+    let mut derived = [0; KEY_LEN];
+    derived.copy_from_slice(&chacha20_block(&[0; KEY_LEN], u32::MAX, &nonce)[..KEY_LEN]);
+
+    for (counter, chunk) in key.chunks(KEY_LEN).enumerate() {
+        let mut material = derived;
+        xor(&mut material[..chunk.len()], chunk);
+        derived.copy_from_slice(&chacha20_block(&material, counter as u32, &nonce)[..KEY_LEN]);
+    }
+
+    derived
+}
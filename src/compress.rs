@@ -0,0 +1,92 @@
+//! Compression of literals into [LZ4 blocks](https://github.com/lz4/lz4/blob/dev/doc/lz4_Block_format.md),
+//! decompressed at runtime by `lz4.rs`.
+
+/// Shortest match encoded by a sequence
+const MIN_MATCH: usize = 4;
+/// Number of trailing bytes always encoded as literals
+const LAST_LITERALS: usize = 5;
+/// Number of trailing bytes in which no match can start
+const MATCH_FIND_LIMIT: usize = 12;
+/// Farthest match, the offset being encoded on 2 bytes
+const MAX_OFFSET: usize = u16::MAX as usize;
+/// Number of bits of the hash table indices
+const HASH_LOG: u32 = 16;
+
+/// Compresses `source` into an LZ4 block
+///
+/// Matches are greedily found through a hash table of the last position of each 4 bytes sequence,
+/// favoring compression speed over ratio.
+pub fn lz4_compress(source: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::with_capacity(source.len() / 2);
+    let mut table = vec![usize::MAX; 1 << HASH_LOG];
+    let match_limit = source.len().saturating_sub(LAST_LITERALS);
+    let mut anchor = 0;
+    let mut i = 0;
+
+    while i + MATCH_FIND_LIMIT <= source.len() {
+        let sequence = read_u32(source, i);
+        let hash = (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize;
+        let candidate = std::mem::replace(&mut table[hash], i);
+
+        if candidate == usize::MAX
+            || i - candidate > MAX_OFFSET
+            || read_u32(source, candidate) != sequence
+        {
+            i += 1;
+            continue;
+        }
+
+        let mut len = MIN_MATCH;
+        while i + len < match_limit && source[candidate + len] == source[i + len] {
+            len += 1;
+        }
+
+        write_sequence(
+            &mut compressed,
+            &source[anchor..i],
+            Some((i - candidate, len)),
+        );
+        i += len;
+        anchor = i;
+    }
+    write_sequence(&mut compressed, &source[anchor..], None);
+
+    compressed
+}
+
+/// Reads a little endian `u32` from `bytes` at `offset`
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Writes a sequence of `literals` followed by an optional match of `(offset, len)`
+fn write_sequence(compressed: &mut Vec<u8>, literals: &[u8], found: Option<(usize, usize)>) {
+    let match_len = found.map_or(0, |(_, len)| len - MIN_MATCH);
+    compressed.push(((literals.len().min(0x0F) << 4) | match_len.min(0x0F)) as u8);
+    write_length(compressed, literals.len());
+    compressed.extend_from_slice(literals);
+
+    if let Some((offset, _)) = found {
+        compressed.extend_from_slice(&(offset as u16).to_le_bytes());
+        write_length(compressed, match_len);
+    }
+}
+
+/// Writes the extra bytes of a length saturating its 4-bit token field
+fn write_length(compressed: &mut Vec<u8>, len: usize) {
+    if len < 0x0F {
+        return;
+    }
+
+    let mut len = len - 0x0F;
+    while len >= 0xFF {
+        compressed.push(0xFF);
+        len -= 0xFF;
+    }
+    compressed.push(len as u8);
+}
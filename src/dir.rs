@@ -0,0 +1,171 @@
+//! # Warning
+//!
+//! This module is emitted as is by `boo_init!()` into the calling crate.
+//! It must only use `core`, as the calling crate may be `no_std`.
+//!
+//! Encrypted directory trees, with their files decrypted by streams.
+
+/// Encrypted directory tree, looked up by path or iterated
+///
+/// Files are sorted by path, each one decrypted piece by piece by a [`BooStream`].
+#[derive(Clone, Copy)]
+pub struct BooDir {
+    files: &'static [BooFile],
+}
+
+impl BooDir {
+    /// Creates a directory over `files`
+    #[inline(always)]
+    pub const fn new(files: &'static [BooFile]) -> Self {
+        Self { files }
+    }
+
+    /// Gets the number of files
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Checks if the directory has no files
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Finds a file by its path relative to the directory, like `"certs/server.pem"`
+    pub fn file(&self, path: &str) -> Option<&'static BooFile> {
+        self.files.iter().find(|file| file.is(path))
+    }
+
+    /// Opens a stream decrypting the file at `path`, relative to the directory
+    ///
+    /// # Returns
+    ///
+    /// `None` if there is no such file
+    pub fn get(&self, path: &str) -> Option<BooStream> {
+        self.file(path).map(BooFile::open)
+    }
+
+    /// Iterates over the files, sorted by path
+    #[inline(always)]
+    pub fn iter(&self) -> ::core::slice::Iter<'static, BooFile> {
+        self.files.iter()
+    }
+}
+
+impl IntoIterator for BooDir {
+    type Item = &'static BooFile;
+    type IntoIter = ::core::slice::Iter<'static, BooFile>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.files.iter()
+    }
+}
+
+impl ::core::fmt::Debug for BooDir {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_list().entries(self.files).finish()
+    }
+}
+
+/// Encrypted file of a [`BooDir`]
+pub struct BooFile {
+    /// Path relative to the directory, encrypted if `name_keys` is set
+    name: &'static [u8],
+    /// Subkey shares and nonce of the encrypted path
+    name_keys: Option<(&'static SubkeyShares, [u8; 12])>,
+    ciphertext: &'static [u8],
+    shares: &'static SubkeyShares,
+    nonce: [u8; 12],
+}
+
+impl BooFile {
+    /// Creates a file named `name`, whose contents are `ciphertext` encrypted with the subkey of `shares`
+    /// and `nonce`
+    #[inline(always)]
+    pub const fn new(
+        name: &'static str,
+        ciphertext: &'static [u8],
+        shares: &'static SubkeyShares,
+        nonce: [u8; 12],
+    ) -> Self {
+        Self {
+            name: name.as_bytes(),
+            name_keys: None,
+            ciphertext,
+            shares,
+            nonce,
+        }
+    }
+
+    /// Creates a file whose name is encrypted with the subkey of `name_shares` and `name_nonce`
+    #[inline(always)]
+    pub const fn with_encrypted_name(
+        name: &'static [u8],
+        name_shares: &'static SubkeyShares,
+        name_nonce: [u8; 12],
+        ciphertext: &'static [u8],
+        shares: &'static SubkeyShares,
+        nonce: [u8; 12],
+    ) -> Self {
+        Self {
+            name,
+            name_keys: Some((name_shares, name_nonce)),
+            ciphertext,
+            shares,
+            nonce,
+        }
+    }
+
+    /// Gets the path relative to the directory
+    ///
+    /// # Returns
+    ///
+    /// `None` if the path is encrypted, only [`BooFile::is`] can then compare it
+    #[inline(always)]
+    pub fn name(&self) -> Option<&'static str> {
+        match self.name_keys {
+            // Safety: plain names are created from a `&str`
+            None => Some(unsafe { ::core::str::from_utf8_unchecked(self.name) }),
+            Some(_) => None,
+        }
+    }
+
+    /// Checks if the file is at `path`, relative to the directory
+    ///
+    /// Encrypted paths are compared in constant time, without being decrypted as a whole.
+    pub fn is(&self, path: &str) -> bool {
+        match &self.name_keys {
+            None => self.name == path.as_bytes(),
+            Some((shares, nonce)) => decrypt_eq(self.name, path.as_bytes(), shares, nonce),
+        }
+    }
+
+    /// Gets the size of the contents in bytes
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.ciphertext.len()
+    }
+
+    /// Checks if the file is empty
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.ciphertext.is_empty()
+    }
+
+    /// Opens a stream decrypting the contents
+    #[inline(always)]
+    pub fn open(&self) -> BooStream {
+        BooStream::new(self.ciphertext, self.shares, self.nonce)
+    }
+}
+
+impl ::core::fmt::Debug for BooFile {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_struct("BooFile")
+            .field("name", &self.name())
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
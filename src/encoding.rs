@@ -0,0 +1,53 @@
+//! Text encodings accepted for the cryptographic key
+
+/// Decodes an hexadecimal string, case insensitive
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, &'static str> {
+    const ERROR: &str = "expected pairs of hexadecimal digits";
+
+    let hex = hex.trim().as_bytes();
+    if !hex.len().is_multiple_of(2) {
+        return Err(ERROR);
+    }
+
+    hex.chunks(2)
+        .map(|pair| {
+            let high = (pair[0] as char).to_digit(16).ok_or(ERROR)?;
+            let low = (pair[1] as char).to_digit(16).ok_or(ERROR)?;
+
+            Ok((high << 4 | low) as u8)
+        })
+        .collect()
+}
+
+/// Decodes a base64 string, using the standard alphabet with optional padding
+pub fn decode_base64(base64: &str) -> Result<Vec<u8>, &'static str> {
+    const ERROR: &str = "expected a standard base64 string";
+
+    let base64 = base64.trim().trim_end_matches('=').as_bytes();
+    if base64.len() % 4 == 1 {
+        return Err(ERROR);
+    }
+
+    let mut bytes = Vec::with_capacity(base64.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &char in base64 {
+        let value = match char {
+            b'A'..=b'Z' => char - b'A',
+            b'a'..=b'z' => char - b'a' + 26,
+            b'0'..=b'9' => char - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(ERROR),
+        };
+
+        buffer = buffer << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(bytes)
+}
@@ -0,0 +1,329 @@
+use proc_macro2::{Delimiter, Group, Span, TokenStream, TokenTree};
+use quote::{ToTokens, quote, quote_spanned};
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    Attribute, Block, ConstParam, Expr, ExprLit, ExprUnary, GenericArgument, Ident, ImplItemConst,
+    ImplItemFn, ItemConst, ItemEnum, ItemFn, ItemMacro, ItemStatic, Lit, Macro, Pat, Path, Token,
+    TraitItemConst, Type, UnOp,
+};
+
+use crate::literal_bytes::LiteralBytes;
+
+/// Standard macros taking a format string among expressions, with the position of the format string
+///
+/// The format string must stay a literal, only the other arguments are encrypted.
+const FORMAT_MACROS: [(&str, usize); 18] = [
+    ("print", 0),
+    ("println", 0),
+    ("eprint", 0),
+    ("eprintln", 0),
+    ("format", 0),
+    ("format_args", 0),
+    ("write", 1),
+    ("writeln", 1),
+    ("panic", 0),
+    ("assert", 1),
+    ("assert_eq", 2),
+    ("assert_ne", 2),
+    ("debug_assert", 1),
+    ("debug_assert_eq", 2),
+    ("debug_assert_ne", 2),
+    ("unreachable", 0),
+    ("todo", 0),
+    ("unimplemented", 0),
+];
+
+/// Crates exporting the standard macros
+const STD_CRATES: [&str; 3] = ["std", "core", "alloc"];
+
+/// Logging macros of the `log` and `tracing` crates, which may start with `key: value` arguments
+const LOG_MACROS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "event"];
+
+/// Crates exporting the logging macros
+const LOG_CRATES: [&str; 2] = ["log", "tracing"];
+
+/// Standard macros requiring literals or patterns as arguments, kept as is
+const LITERAL_MACROS: [&str; 15] = [
+    "asm",
+    "naked_asm",
+    "cfg",
+    "compile_error",
+    "concat",
+    "concat_bytes",
+    "env",
+    "option_env",
+    "include",
+    "include_bytes",
+    "include_str",
+    "stringify",
+    "matches",
+    "assert_matches",
+    "debug_assert_matches",
+];
+
+/// Replaces the literals of an item by their encrypted form, like `boo!()` does
+///
+/// Strings, byte strings, C-strings and numbers are encrypted, except in const contexts
+/// (`const` and `static` items, `const fn`, `const` blocks, array lengths, enum discriminants,
+/// generic arguments and defaults), patterns and attributes, where a runtime value is not allowed.
+///
+/// The arguments of other macros are encrypted when they are expressions separated by commas, keeping
+/// their first string literal as a possible format string. Otherwise, the macro is kept as is and a
+/// warning is emitted in its function.
+#[derive(Default)]
+pub struct LiteralEncryptor {
+    /// Macros whose arguments could not be parsed, in the function being visited
+    warnings: Vec<Span>,
+}
+
+impl LiteralEncryptor {
+    /// Encrypts `expr` if it is an eligible literal, keeping its type
+    ///
+    /// # Returns
+    ///
+    /// `None` if the expression is not an eligible literal
+    fn encrypt(expr: &Expr) -> Option<Expr> {
+        let lit = match expr {
+            Expr::Lit(ExprLit { attrs, lit }) if attrs.is_empty() => lit,
+            Expr::Unary(ExprUnary {
+                attrs,
+                op: UnOp::Neg(_),
+                expr,
+            }) if attrs.is_empty() => match &**expr {
+                Expr::Lit(ExprLit { lit, .. }) if matches!(lit, Lit::Int(_) | Lit::Float(_)) => lit,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        // Invalid literals are kept as is, for the compiler to report them
+        let literal = LiteralBytes::parse(expr.to_token_stream()).ok()?;
+        // Located at the literal, but still from the expansion, for lints to skip the generated code
+        let span = Span::call_site().located_at(lit.span());
+
+        // Strings are borrowed from a static decrypted on first use, living as long as a literal does
+        let encrypted = match lit {
+            Lit::Str(_) | Lit::CStr(_) | Lit::ByteStr(_) => {
+                let deref = match lit {
+                    Lit::ByteStr(_) => quote! { & * },
+                    _ => quote! { & ** },
+                };
+                let (ty, init) = literal.encrypt_static(&syn::parse_quote!(_));
+                Self::respan(
+                    quote! {{
+                        static BOO_LITERAL: #ty = #init;
+                        #deref BOO_LITERAL
+                    }},
+                    span,
+                )
+            }
+            Lit::Int(_) | Lit::Float(_) => Self::respan(literal.encrypt(), span),
+            _ => return None,
+        };
+
+        // Parenthesized, since a block at the start of a statement would end the expression
+        Some(Expr::Verbatim(quote_spanned! { span=>
+            (#encrypted)
+        }))
+    }
+
+    /// Sets the span of every token of `tokens` to `span`, for errors to point at the literal instead of
+    /// the attribute
+    ///
+    /// Invisible groups hold the literal itself, checked against its inferred type: they keep their source
+    /// spans, for the compiler to report overflows as in the calling code.
+    fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+        tokens
+            .into_iter()
+            .map(|mut token| {
+                if let TokenTree::Group(group) = &token
+                    && group.delimiter() == Delimiter::None
+                {
+                    return token;
+                }
+                if let TokenTree::Group(group) = &token {
+                    token = TokenTree::Group(Group::new(
+                        group.delimiter(),
+                        Self::respan(group.stream(), span),
+                    ));
+                }
+                token.set_span(span);
+                token
+            })
+            .collect()
+    }
+
+    /// Gets the position of the format string among the arguments of the macro at `path`
+    ///
+    /// The first string literal of other macros is kept too, since it may be a format string.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the macro takes no format string
+    fn format_index(path: &Path, args: &Punctuated<Expr, Token![,]>) -> Option<usize> {
+        if let Some((name, krate)) = Self::macro_name(path)
+            && krate.is_none_or(|krate| STD_CRATES.contains(&krate.as_str()))
+            && let Some((_, index)) = FORMAT_MACROS.iter().find(|(n, _)| *n == name)
+        {
+            return Some(*index);
+        }
+
+        args.iter().position(|arg| {
+            matches!(
+                arg,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(_),
+                    ..
+                })
+            )
+        })
+    }
+
+    /// Gets the name of the macro at `path`, with its crate if it is called by a two segments path
+    fn macro_name(path: &Path) -> Option<(String, Option<String>)> {
+        let mut segments = path.segments.iter().map(|s| s.ident.to_string());
+        match (segments.next(), segments.next(), segments.next()) {
+            (Some(name), None, _) => Some((name, None)),
+            (Some(krate), Some(name), None) => Some((name, Some(krate))),
+            _ => None,
+        }
+    }
+
+    /// Checks if the macro at `path` is a logging macro, called by its name or its full path
+    fn is_log_macro(path: &Path) -> bool {
+        Self::macro_name(path).is_some_and(|(name, krate)| {
+            LOG_MACROS.contains(&name.as_str())
+                && krate.is_none_or(|krate| LOG_CRATES.contains(&krate.as_str()))
+        })
+    }
+
+    /// Parses the arguments of a macro, expressions separated by commas
+    ///
+    /// With `log`, leading `key: value` arguments like `target: "app"` are returned as is, before the
+    /// expressions.
+    fn parse_args(
+        input: ParseStream,
+        log: bool,
+    ) -> syn::Result<(TokenStream, Punctuated<Expr, Token![,]>)> {
+        let mut prefix = TokenStream::new();
+        while log && input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let key = input.parse::<Ident>()?;
+            let colon = input.parse::<Token![:]>()?;
+            let value = input.parse::<Expr>()?;
+            let comma = input.parse::<Option<Token![,]>>()?;
+            prefix.extend(quote! { #key #colon #value #comma });
+        }
+
+        Ok((prefix, Punctuated::parse_terminated(input)?))
+    }
+
+    /// Prepends to `block` a warning for each macro whose arguments could not be parsed
+    fn warn(&mut self, block: &mut Block) {
+        let warnings = self.warnings.drain(..).map(|span| {
+            let warning = quote_spanned!(span=> BooWarning);
+            syn::parse_quote! {
+                const _: () = {
+                    #[deprecated(note = "the literals of this macro are not encrypted, its arguments are not expressions")]
+                    struct BooWarning;
+                    let _ = #warning;
+                };
+            }
+        });
+        block.stmts.splice(0..0, warnings);
+    }
+}
+
+impl VisitMut for LiteralEncryptor {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(encrypted) = Self::encrypt(expr) {
+            *expr = encrypted;
+            return;
+        }
+
+        match expr {
+            Expr::Const(_) => {}
+            Expr::Repeat(repeat) => self.visit_expr_mut(&mut repeat.expr),
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut Macro) {
+        let Some(name) = mac
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+        else {
+            return;
+        };
+        // The literals of Boo macros are already encrypted
+        if LITERAL_MACROS.contains(&name.as_str()) || name.starts_with("boo") {
+            return;
+        }
+
+        let log = Self::is_log_macro(&mac.path);
+        if let Ok((prefix, mut args)) =
+            mac.parse_body_with(|input: ParseStream| Self::parse_args(input, log))
+        {
+            let format_index = Self::format_index(&mac.path, &args);
+            for (i, arg) in args.iter_mut().enumerate() {
+                if Some(i) != format_index {
+                    self.visit_expr_mut(arg);
+                }
+            }
+
+            mac.tokens = quote! { #prefix #args };
+        } else if let Ok((mut value, semi, len)) = mac.parse_body_with(|input: ParseStream| {
+            Ok((
+                input.parse::<Expr>()?,
+                input.parse::<Token![;]>()?,
+                input.parse::<Expr>()?,
+            ))
+        }) {
+            // Repeated values like `vec![0; len]`, whose length may be a const context
+            self.visit_expr_mut(&mut value);
+            mac.tokens = quote! { #value #semi #len };
+        } else if !mac.tokens.is_empty() {
+            self.warnings.push(mac.path.span());
+        }
+    }
+
+    fn visit_item_fn_mut(&mut self, item: &mut ItemFn) {
+        if item.sig.constness.is_none() {
+            visit_mut::visit_item_fn_mut(self, item);
+            self.warn(&mut item.block);
+        }
+    }
+
+    fn visit_impl_item_fn_mut(&mut self, item: &mut ImplItemFn) {
+        if item.sig.constness.is_none() {
+            visit_mut::visit_impl_item_fn_mut(self, item);
+            self.warn(&mut item.block);
+        }
+    }
+
+    fn visit_attribute_mut(&mut self, _: &mut Attribute) {}
+
+    fn visit_const_param_mut(&mut self, _: &mut ConstParam) {}
+
+    fn visit_generic_argument_mut(&mut self, _: &mut GenericArgument) {}
+
+    fn visit_impl_item_const_mut(&mut self, _: &mut ImplItemConst) {}
+
+    fn visit_item_const_mut(&mut self, _: &mut ItemConst) {}
+
+    fn visit_item_enum_mut(&mut self, _: &mut ItemEnum) {}
+
+    fn visit_item_macro_mut(&mut self, _: &mut ItemMacro) {}
+
+    fn visit_item_static_mut(&mut self, _: &mut ItemStatic) {}
+
+    fn visit_pat_mut(&mut self, _: &mut Pat) {}
+
+    fn visit_trait_item_const_mut(&mut self, _: &mut TraitItemConst) {}
+
+    fn visit_type_mut(&mut self, _: &mut Type) {}
+}
@@ -0,0 +1,193 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{Expr, ExprLit, Lit, Token};
+
+use crate::literal_bytes::LiteralBytes;
+
+/// Format string error message
+const FORMAT_ERROR: &str = r#"expected a format string literal (like `"Hello {}"`)"#;
+
+/// Part of a format string
+#[derive(Debug, PartialEq)]
+pub enum FormatPiece {
+    /// Literal text, unescaped
+    Text(String),
+    /// Placeholder, kept as is including its braces: `{}`, `{0:?}`, `{name:>8}`...
+    Placeholder(String),
+}
+
+/// Splits a format string into its literal text and its placeholders
+///
+/// # Returns
+///
+/// An error if the braces are unbalanced
+pub fn split_format(format: &str) -> Result<Vec<FormatPiece>, &'static str> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = format.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.next_if_eq(&'{').is_some() => text.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => text.push('}'),
+            '{' => {
+                let mut placeholder = String::from('{');
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(char) => placeholder.push(char),
+                        None => return Err("invalid format string: expected `}`"),
+                    }
+                }
+                placeholder.push('}');
+
+                if !text.is_empty() {
+                    pieces.push(FormatPiece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(FormatPiece::Placeholder(placeholder));
+            }
+            '}' => return Err("invalid format string: unmatched `}`"),
+            char => text.push(char),
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(FormatPiece::Text(text));
+    }
+
+    Ok(pieces)
+}
+
+/// Gets the indices of the positional arguments referenced by the placeholders of a format string
+///
+/// Implicit (`{}`), explicit (`{1}`), width and precision (`{:1$}`, `{:.*}`) references are included,
+/// named and captured arguments are not.
+fn positional_references(pieces: &[FormatPiece]) -> Vec<usize> {
+    let mut references = Vec::new();
+    let mut next = 0;
+
+    for piece in pieces {
+        let FormatPiece::Placeholder(placeholder) = piece else {
+            continue;
+        };
+        let inner = &placeholder[1..placeholder.len() - 1];
+        let (argument, spec) = inner.split_once(':').unwrap_or((inner, ""));
+
+        // The fill character may be a digit, only an alignment follows it
+        let spec = match spec.char_indices().nth(1) {
+            Some((i, '<' | '^' | '>')) => &spec[i + 1..],
+            _ => spec.strip_prefix(['<', '^', '>']).unwrap_or(spec),
+        };
+
+        // Width and precision arguments, like `1$`
+        for (end, _) in spec.match_indices('$') {
+            let digits = spec[..end]
+                .bytes()
+                .rev()
+                .take_while(u8::is_ascii_digit)
+                .count();
+            if let Ok(index) = spec[end - digits..end].parse() {
+                references.push(index);
+            }
+        }
+
+        // The precision of `.*` is taken before the value
+        if spec.contains(".*") {
+            references.push(next);
+            next += 1;
+        }
+
+        match argument.trim() {
+            "" => {
+                references.push(next);
+                next += 1;
+            }
+            argument => references.extend(argument.parse::<usize>().ok()),
+        }
+    }
+
+    references
+}
+
+/// Encrypts the literal text of a formatting macro arguments
+///
+/// The text is moved into encrypted named arguments, leaving only the placeholders in the format string.
+/// User placeholders are kept as is, so that implicit, positional, named and captured arguments still work.
+///
+/// # Arguments
+///
+/// * `tokens` - Arguments of the formatting macro
+/// * `destination` - Whether the format string is preceded by a destination, like with `write!()`
+///
+/// # Returns
+///
+/// The new arguments of the formatting macro
+pub fn encrypt_format(tokens: TokenStream, destination: bool) -> syn::Result<TokenStream> {
+    let args = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(tokens.clone())?;
+    let mut args = args.into_iter();
+
+    let destination = match destination {
+        true => match args.next() {
+            Some(destination) => Some(quote! { #destination, }),
+            None => return Err(syn::Error::new_spanned(tokens, "expected a destination")),
+        },
+        false => None,
+    };
+
+    let Some(format) = args.next() else {
+        return Ok(destination.into_iter().collect());
+    };
+    let Expr::Lit(ExprLit {
+        lit: Lit::Str(format),
+        ..
+    }) = format
+    else {
+        return Err(syn::Error::new_spanned(format, FORMAT_ERROR));
+    };
+
+    let pieces =
+        split_format(&format.value()).map_err(|err| syn::Error::new_spanned(&format, err))?;
+
+    // The encrypted text is passed as extra arguments, which must not be reachable by position
+    let args = args.collect::<Vec<_>>();
+    if let Some(index) = positional_references(&pieces)
+        .into_iter()
+        .find(|index| *index >= args.len())
+    {
+        let given = match args.len() {
+            0 => "there are no arguments".to_owned(),
+            1 => "there is 1 argument".to_owned(),
+            count => format!("there are {count} arguments"),
+        };
+        return Err(syn::Error::new_spanned(
+            &format,
+            format!("invalid reference to positional argument {index} ({given})"),
+        ));
+    }
+
+    let mut format_string = String::new();
+    let mut texts = Vec::new();
+    for piece in pieces {
+        match piece {
+            FormatPiece::Text(text) => {
+                let name = format_ident!("__boo_text_{}", texts.len());
+                format_string.push_str(&format!("{{{name}}}"));
+                let text = LiteralBytes::Str {
+                    capacity: text.len(),
+                    bytes: text.into_bytes(),
+                }
+                .encrypt();
+                texts.push(quote! { #name = #text });
+            }
+            FormatPiece::Placeholder(placeholder) => format_string.push_str(&placeholder),
+        }
+    }
+
+    // The original span resolves the arguments captured by the format string at the call site
+    let mut format_string = Literal::string(&format_string);
+    format_string.set_span(format.span());
+
+    Ok(quote! { #destination #format_string, #(#args,)* #(#texts),* })
+}
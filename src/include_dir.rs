@@ -0,0 +1,82 @@
+use std::path::{Component, Path, PathBuf};
+use std::{fs, io};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::literal_bytes::LiteralBytes;
+
+/// Lists the files of a directory tree, recursively
+///
+/// Symbolic links to files are followed, but not symbolic links to directories, which could form a cycle.
+///
+/// # Returns
+///
+/// The files sorted by name, each name being the path relative to `root` with `/` separators,
+/// or an error if a directory can't be read or a name is not UTF-8
+pub fn walk_dir(root: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if file_type.is_symlink() && path.is_dir() {
+                continue;
+            }
+
+            let name = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .filter_map(|component| match component {
+                    Component::Normal(name) => Some(name.to_str()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("`{}` is not UTF-8", path.display()),
+                    )
+                })?
+                .join("/");
+            files.push((name, path));
+        }
+    }
+    files.sort();
+
+    Ok(files)
+}
+
+/// Encrypts the files of a directory inside a `BooDir`
+///
+/// # Arguments
+///
+/// * `files` - Names and contents of the files, sorted by name
+/// * `encrypt_names` - Whether the names are encrypted too, only comparable at runtime
+pub fn encrypt_dir(files: Vec<(String, Vec<u8>)>, encrypt_names: bool) -> TokenStream {
+    let len = files.len();
+    let files = files.into_iter().map(|(name, data)| {
+        let contents = LiteralBytes::ByteStr(data).encrypt_parts();
+
+        match encrypt_names {
+            true => {
+                let name = LiteralBytes::ByteStr(name.into_bytes()).encrypt_parts();
+                quote! { crate::__boo::BooFile::with_encrypted_name(#name, #contents) }
+            }
+            false => quote! { crate::__boo::BooFile::new(#name, #contents) },
+        }
+    });
+
+    quote! {{
+        static FILES: [crate::__boo::BooFile; #len] = [#(#files),*];
+        crate::__boo::BooDir::new(&FILES)
+    }}
+}
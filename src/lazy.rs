@@ -0,0 +1,131 @@
+//! # Warning
+//!
+//! This module is emitted as is by `boo_init!()` into the calling crate.
+//! It must only use `core`, as the calling crate may be `no_std`.
+//!
+//! Encrypted statics, decrypted once on first access.
+
+/// The value is encrypted
+const ENCRYPTED: u8 = 0;
+/// The value is being decrypted by a thread
+const DECRYPTING: u8 = 1;
+/// The value is decrypted
+const DECRYPTED: u8 = 2;
+
+/// Encrypted static, decrypted on first access and kept decrypted until [`BooStatic::reset`]
+///
+/// Waiting threads spin while the value is decrypted, so that no `std` synchronization is required.
+pub struct BooStatic<T> {
+    state: ::core::sync::atomic::AtomicU8,
+    value: ::core::cell::UnsafeCell<::core::mem::MaybeUninit<T>>,
+    decrypt: fn() -> T,
+}
+
+// Safety: the value is only written once, while the other threads wait for the `DECRYPTED` state
+unsafe impl<T: Send + Sync> Sync for BooStatic<T> {}
+
+impl<T> BooStatic<T> {
+    /// Creates an encrypted static, decrypted by `decrypt`
+    #[inline(always)]
+    pub const fn new(decrypt: fn() -> T) -> Self {
+        Self {
+            state: ::core::sync::atomic::AtomicU8::new(ENCRYPTED),
+            value: ::core::cell::UnsafeCell::new(::core::mem::MaybeUninit::uninit()),
+            decrypt,
+        }
+    }
+
+    /// Gets the decrypted value, decrypting it on first access
+    pub fn get(&self) -> &T {
+        loop {
+            match self.state.compare_exchange_weak(
+                ENCRYPTED,
+                DECRYPTING,
+                ::core::sync::atomic::Ordering::Acquire,
+                ::core::sync::atomic::Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // Lets another access decrypt the value if `decrypt` panics
+                    let guard = DecryptingGuard(&self.state);
+                    let value = (self.decrypt)();
+                    ::core::mem::forget(guard);
+                    // Safety: the `DECRYPTING` state grants exclusive access
+                    unsafe { (*self.value.get()).write(value) };
+                    self.state
+                        .store(DECRYPTED, ::core::sync::atomic::Ordering::Release);
+                }
+                // Safety: the value is initialized and no longer written
+                Err(DECRYPTED) => return unsafe { (*self.value.get()).assume_init_ref() },
+                Err(_) => ::core::hint::spin_loop(),
+            }
+        }
+    }
+
+    /// Checks if the value is currently decrypted
+    #[inline(always)]
+    pub fn is_decrypted(&self) -> bool {
+        self.state.load(::core::sync::atomic::Ordering::Acquire) == DECRYPTED
+    }
+
+    /// Drops and wipes the decrypted value, the next access decrypting it again
+    ///
+    /// # Safety
+    ///
+    /// No reference returned by [`BooStatic::get`] or `deref` must be alive,
+    /// and no other thread may access the static during the call.
+    pub unsafe fn reset(&self) {
+        if self
+            .state
+            .compare_exchange(
+                DECRYPTED,
+                DECRYPTING,
+                ::core::sync::atomic::Ordering::Acquire,
+                ::core::sync::atomic::Ordering::Acquire,
+            )
+            .is_ok()
+        {
+            // Safety: the value is initialized, and the caller guarantees exclusive access
+            unsafe {
+                (*self.value.get()).assume_init_drop();
+                wipe_value(&mut *self.value.get());
+            }
+            self.state
+                .store(ENCRYPTED, ::core::sync::atomic::Ordering::Release);
+        }
+    }
+}
+
+impl<T> ::core::ops::Deref for BooStatic<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T> Drop for BooStatic<T> {
+    fn drop(&mut self) {
+        // Safety: `&mut self` guarantees exclusive access
+        unsafe { self.reset() };
+    }
+}
+
+/// Guard resetting the state of a [`BooStatic`] to `ENCRYPTED` when dropped during its decryption
+struct DecryptingGuard<'a>(&'a ::core::sync::atomic::AtomicU8);
+
+impl Drop for DecryptingGuard<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.0
+            .store(ENCRYPTED, ::core::sync::atomic::Ordering::Release);
+    }
+}
+
+impl<T> ::core::fmt::Debug for BooStatic<T> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_struct("BooStatic")
+            .field("decrypted", &self.is_decrypted())
+            .finish_non_exhaustive()
+    }
+}
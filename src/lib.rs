@@ -8,48 +8,259 @@ extern crate quote;
 extern crate rand;
 extern crate syn;
 
+use std::env::{self, VarError};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 
-use proc_macro2::Literal;
-use quote::quote;
-use syn::{Expr, ExprLit, Lit};
+use proc_macro2::Span;
+use quote::{ToTokens, quote};
+use syn::parse::{ParseStream, Parser};
+use syn::visit_mut::VisitMut;
+use syn::{Expr, Ident, Item, ItemStatic, LitStr, StaticMutability, Token};
 
+use crate::cipher::Cipher;
+use crate::encrypt_literals::LiteralEncryptor;
+use crate::format::encrypt_format;
+use crate::include_dir::{encrypt_dir, walk_dir};
 use crate::literal_bytes::LiteralBytes;
+use crate::random::{Random, SEED};
 
+mod chacha20;
+mod cipher;
+mod compress;
+mod encoding;
+mod encrypt_literals;
+mod format;
+mod include_dir;
 mod literal_bytes;
+mod lz4;
+mod number;
+mod random;
 #[cfg(test)]
 mod test;
 mod utils;
 
-const INCLUDE_ERROR: &str = r#"expected one file path (ex. "data.txt")"#;
+const INCLUDE_ERROR: &str =
+    r#"expected one file path (ex. "data.txt" or manifest = "assets/data.txt")"#;
+const INCLUDE_DIR_ERROR: &str =
+    r#"expected one directory path and optional flags (ex. "assets/" or "assets/", encrypt_names)"#;
+const EQ_ERROR: &str = r#"expected one value and one string literal (ex. input, "password")"#;
+const ENCRYPT_LITERALS_ERROR: &str = "expected a function, an impl block or a module";
+const STATIC_MUT_ERROR: &str =
+    "encrypted statics are immutable, `reset()` wipes their decrypted value";
+const WITH_ERROR: &str =
+    r#"expected one literal and one closure (ex. "secret", |s: &str| s.len())"#;
+
+/// Minimum length of the cryptographic key in bytes
+const MIN_KEY_LEN: usize = 16;
+
+/// Decodes the value of a key environment variable into the key bytes
+type KeyDecoder = fn(String) -> Result<Vec<u8>, String>;
+
+/// Environment variables the cryptographic key can be read from, with their decoder
+const KEY_SOURCES: [(&str, KeyDecoder); 4] = [
+    ("BOO_KEY", |key| Ok(key.into_bytes())),
+    ("BOO_KEY_FILE", |path| {
+        fs::read(manifest_path(&path))
+            .map_err(|err| format!("failed to read `BOO_KEY_FILE`: {err}"))
+    }),
+    ("BOO_KEY_HEX", |hex| {
+        encoding::decode_hex(&hex).map_err(|err| format!("invalid `BOO_KEY_HEX`: {err}"))
+    }),
+    ("BOO_KEY_BASE64", |base64| {
+        encoding::decode_base64(&base64).map_err(|err| format!("invalid `BOO_KEY_BASE64`: {err}"))
+    }),
+];
 
 /// Cryptographic key
-static KEY: LazyLock<Box<[u8]>> = LazyLock::new(|| match option_env!("BOO_KEY") {
-    Some(key) => key.as_bytes().into(),
-    None => {
-        let mut key = [0; 64];
-        rand::fill(&mut key);
+///
+/// Read from the environment of the crate expanding the macros, not the one building this crate.
+static KEY: LazyLock<Result<Box<[u8]>, String>> = LazyLock::new(|| {
+    // Checked here to report seed errors along with key errors
+    SEED.as_ref().map_err(Clone::clone)?;
 
-        key.into()
+    let key = match read_key()? {
+        Some(key) => key,
+        None => {
+            let mut key = vec![0; 64];
+            Random::new("key").fill(&mut key);
+
+            key
+        }
+    };
+
+    if key.len() < MIN_KEY_LEN {
+        return Err(format!(
+            "the key must be at least {MIN_KEY_LEN} bytes long, got {}",
+            key.len()
+        ));
     }
+
+    Ok(key.into())
 });
 
+/// Reads the cryptographic key from one of the [`KEY_SOURCES`] environment variables
+///
+/// # Returns
+///
+/// `None` if no variable is set
+fn read_key() -> Result<Option<Vec<u8>>, String> {
+    let mut key = None;
+    for (name, decode) in KEY_SOURCES {
+        let value = match env::var(name) {
+            Ok(value) => value,
+            Err(VarError::NotPresent) => continue,
+            Err(VarError::NotUnicode(_)) => return Err(format!("`{name}` must be valid UTF-8")),
+        };
+
+        if key.is_some() {
+            let names = KEY_SOURCES.map(|(name, _)| format!("`{name}`")).join(", ");
+            return Err(format!("only one of {names} can be set"));
+        }
+
+        key = Some(decode(value)?);
+    }
+
+    Ok(key)
+}
+
+/// Makes a path relative to the calling crate manifest directory, like the `BOO_KEY_FILE` path
+fn manifest_path(path: &str) -> PathBuf {
+    match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(manifest_dir) => Path::new(&manifest_dir).join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Gets the cryptographic key
+///
+/// # Panics
+///
+/// If the key is invalid, macros must check [`key_error`] before expanding.
+fn key() -> &'static [u8] {
+    KEY.as_deref().expect("invalid key")
+}
+
+/// Gets the compile error to emit if the cryptographic key is invalid
+fn key_error() -> Option<proc_macro::TokenStream> {
+    let err = KEY.as_ref().err()?;
+
+    Some(
+        syn::Error::new(Span::call_site(), err)
+            .to_compile_error()
+            .into(),
+    )
+}
+
 /// Initialize the boo library allowing use of the boo macros.
 ///
-/// Optionally set a custom key using the `BOO_KEY` environment variable.
+/// Optionally set a custom key of at least 16 bytes, read when compiling the calling crate, using one of:
+///
+/// - `BOO_KEY`: the raw UTF-8 key
+/// - `BOO_KEY_FILE`: path to a binary key file, relative to the calling crate manifest directory
+/// - `BOO_KEY_HEX`: the hexadecimal encoded key
+/// - `BOO_KEY_BASE64`: the base64 encoded key
+///
 /// Fallbacks to a random 64-bytes cryptographic key.
+///
+/// Set `BOO_SEED` to derive the fallback key and all the random values deterministically,
+/// so that two builds of the same sources produce identical binaries.
+///
+/// The cipher is selected with cargo features: repeating-key XOR by default,
+/// ChaCha20 with the `chacha20` feature.
 #[proc_macro]
 pub fn boo_init(_tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let key = Literal::byte_string(&KEY);
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    let cipher = Cipher::SELECTED;
+    let key = cipher.runtime();
     let utils = syn::parse_str::<syn::File>(include_str!("utils.rs")).unwrap();
+    let strings = syn::parse_str::<syn::File>(include_str!("strings.rs"))
+        .unwrap()
+        .items;
+    let lazy = syn::parse_str::<syn::File>(include_str!("lazy.rs"))
+        .unwrap()
+        .items;
+    let stream = syn::parse_str::<syn::File>(include_str!("stream.rs"))
+        .unwrap()
+        .items;
+    let blob = syn::parse_str::<syn::File>(include_str!("blob.rs"))
+        .unwrap()
+        .items;
+    let dir = syn::parse_str::<syn::File>(include_str!("dir.rs"))
+        .unwrap()
+        .items;
+    let lz4 = syn::parse_str::<syn::File>(include_str!("lz4.rs"))
+        .unwrap()
+        .items;
+    // The calling crate can only be assumed to link `std` if requested
+    let stream_read = cfg!(feature = "std").then(|| {
+        quote! {
+            impl ::std::io::Read for BooStream {
+                fn read(&mut self, buf: &mut [u8]) -> ::std::io::Result<usize> {
+                    Ok(BooStream::read(self, buf))
+                }
+            }
+        }
+    });
+    // Same for `alloc`, only linked by `std` crates when declared
+    let blob_alloc = cfg!(feature = "alloc").then(|| {
+        quote! {
+            extern crate alloc;
+
+            impl BooBlob {
+                /// Decrypts the bytes into a new `Vec`, which is not wiped when dropped
+                pub fn decrypt_to_vec(&self) -> alloc::vec::Vec<u8> {
+                    let mut bytes = alloc::vec![0; self.len()];
+                    self.decrypt_into(&mut bytes);
+                    bytes
+                }
+            }
+
+            impl BooStrBlob {
+                /// Decrypts the string into a new `String`, which is not wiped when dropped
+                pub fn decrypt_to_string(&self) -> alloc::string::String {
+                    // Safety: the bytes of a `BooStrBlob` are UTF-8
+                    unsafe { alloc::string::String::from_utf8_unchecked(self.as_bytes().decrypt_to_vec()) }
+                }
+            }
+        }
+    });
+    let chacha20 = match cipher {
+        Cipher::ChaCha20 => {
+            syn::parse_str::<syn::File>(include_str!("chacha20.rs"))
+                .unwrap()
+                .items
+        }
+        Cipher::Xor => Vec::new(),
+    };
+
+    let key_vars = KEY_SOURCES.map(|(name, _)| name);
+    let key_file = env::var("BOO_KEY_FILE")
+        .ok()
+        .map(|path| track_file(&manifest_path(&path)));
 
     let result = quote! {
-        static BOO_KEY: &[u8] = #key;
+        // Tracks the key, so that cargo rebuilds the crate when it changes
+        #(const _: Option<&str> = option_env!(#key_vars);)*
+        const _: Option<&str> = option_env!("BOO_SEED");
+        #key_file
 
         pub mod __boo {
             #utils
+            #(#strings)*
+            #(#stream)*
+            #stream_read
+            #(#blob)*
+            #blob_alloc
+            #(#dir)*
+            #(#lz4)*
+            #(#lazy)*
+            #(#chacha20)*
+            #key
         }
     };
 
@@ -59,69 +270,505 @@ pub fn boo_init(_tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// Encrypts a literal
 #[proc_macro]
 pub fn boo(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
     let literal = match LiteralBytes::parse(tokens.into()) {
         Ok(literal) => literal,
-        Err(err) => panic!("{err}"),
+        Err(err) => return expr_error(err),
+    };
+
+    literal.encrypt().into()
+}
+
+/// Encrypts a literal, lending its decrypted value to a closure: `boo_with!("secret", |s: &str| ...)`
+///
+/// The decrypted value is wiped when the closure returns.
+#[proc_macro]
+pub fn boo_with(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    let parser = |input: ParseStream| {
+        let literal = input.parse::<Expr>()?;
+        input.parse::<Token![,]>()?;
+        let closure = input.parse::<Expr>()?;
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok((literal, closure))
+    };
+    let (literal, closure) = match parser.parse(tokens) {
+        Ok(args) => args,
+        Err(err) => return expr_error(syn::Error::new(err.span(), WITH_ERROR)),
+    };
+
+    let literal = match LiteralBytes::parse(literal.into_token_stream()) {
+        Ok(literal) => literal,
+        Err(err) => return expr_error(err),
+    };
+
+    literal.encrypt_with(&closure).into()
+}
+
+/// Compares a runtime value with an encrypted string literal, in constant time: `boo_eq!(input, "password")`
+///
+/// The value can be anything implementing `AsRef<[u8]>`, and the literal a string or a byte string.
+/// The literal is decrypted one byte at a time during the comparison, never as a whole.
+#[proc_macro]
+pub fn boo_eq(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    let parser = |input: ParseStream| {
+        let value = input.parse::<Expr>()?;
+        input.parse::<Token![,]>()?;
+        let literal = input.parse::<Expr>()?;
+        input.parse::<Option<Token![,]>>()?;
+
+        Ok((value, literal))
+    };
+    let (value, literal) = match parser.parse(tokens) {
+        Ok(args) => args,
+        Err(err) => return expr_error(syn::Error::new(err.span(), EQ_ERROR)),
+    };
+
+    let literal_tokens = literal.to_token_stream();
+    let encrypted = LiteralBytes::parse(literal_tokens.clone()).and_then(|literal| {
+        literal
+            .encrypt_eq(&value)
+            .map_err(|err| syn::Error::new_spanned(literal_tokens, err))
+    });
+
+    match encrypted {
+        Ok(encrypted) => encrypted.into(),
+        Err(err) => expr_error(err),
+    }
+}
+
+/// Declares encrypted statics, decrypted on first access: `boo_static!(static API_URL: &str = "...");`
+///
+/// Strings are stored decrypted in their stack types, dereferencing to the declared type.
+#[proc_macro]
+pub fn boo_static(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    let parser = |input: ParseStream| {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse::<ItemStatic>()?);
+        }
+
+        Ok(items)
+    };
+    let items = match parser.parse(tokens) {
+        Ok(items) => items,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let mut statics = Vec::with_capacity(items.len());
+    for item in items {
+        if let StaticMutability::Mut(mutability) = item.mutability {
+            return syn::Error::new_spanned(mutability, STATIC_MUT_ERROR)
+                .to_compile_error()
+                .into();
+        }
+
+        let literal = match LiteralBytes::parse(item.expr.into_token_stream()) {
+            Ok(literal) => literal,
+            Err(err) => return err.to_compile_error().into(),
+        };
+
+        let ItemStatic {
+            attrs, vis, ident, ..
+        } = item;
+        let (ty, init) = literal.encrypt_static(&item.ty);
+        statics.push(quote! {
+            #(#attrs)*
+            #vis static #ident: #ty = #init;
+        });
+    }
+
+    quote! { #(#statics)* }.into()
+}
+
+/// Encrypts the literals of a function, an impl block or a module, like `boo!()` does
+///
+/// Strings, byte strings, C-strings and numbers are encrypted, including the arguments of formatting macros,
+/// except where a runtime value is not allowed (const contexts, patterns and attributes).
+/// Strings are decrypted into statics on first use, borrowed for the `'static` lifetime like literals.
+#[proc_macro_attribute]
+pub fn encrypt_literals(
+    attr: proc_macro::TokenStream,
+    item: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    if let Some(attr) = proc_macro2::TokenStream::from(attr).into_iter().next() {
+        return syn::Error::new_spanned(attr, "expected no arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let mut item = match syn::parse::<Item>(item) {
+        Ok(item @ (Item::Fn(_) | Item::Impl(_) | Item::Mod(_))) => item,
+        Ok(item) => {
+            return syn::Error::new_spanned(item, ENCRYPT_LITERALS_ERROR)
+                .to_compile_error()
+                .into();
+        }
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    LiteralEncryptor::default().visit_item_mut(&mut item);
+
+    item.into_token_stream().into()
+}
+
+/// Formats a string like `format!()`, encrypting the text of the format string
+///
+/// `format!()` is resolved at the call site: `no_std` crates must import it with `use alloc::format;`.
+#[proc_macro]
+pub fn boo_format(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    format_macro(tokens, quote! { format }, false)
+}
+
+/// Prints to the standard output like `print!()`, encrypting the text of the format string
+#[proc_macro]
+pub fn boo_print(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    format_macro(tokens, quote! { print }, false)
+}
+
+/// Prints to the standard output like `println!()`, encrypting the text of the format string
+#[proc_macro]
+pub fn boo_println(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    format_macro(tokens, quote! { println }, false)
+}
+
+/// Writes into a buffer like `write!()`, encrypting the text of the format string
+#[proc_macro]
+pub fn boo_write(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    format_macro(tokens, quote! { write }, true)
+}
+
+/// Writes into a buffer like `writeln!()`, encrypting the text of the format string
+#[proc_macro]
+pub fn boo_writeln(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    format_macro(tokens, quote! { writeln }, true)
+}
+
+/// Panics like `panic!()`, encrypting the text of the format string
+#[proc_macro]
+pub fn boo_panic(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    format_macro(tokens, quote! { panic }, false)
+}
+
+/// Encrypts a string literal as a null-terminated UTF-16 string, for wide string APIs
+#[proc_macro]
+pub fn boo_wide(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    let literal = match LiteralBytes::parse_wide(tokens.into()) {
+        Ok(literal) => literal,
+        Err(err) => return expr_error(err),
     };
 
     literal.encrypt().into()
 }
 
 /// Encrypts a raw file as bytes
+///
+/// Whatever its size, the file is decrypted by a `BooBlob` into a caller buffer.
 #[proc_macro]
 pub fn boo_include_bytes(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let Some(file_path) = read_literal_str(tokens) else {
-        panic!("{INCLUDE_ERROR}");
-    };
-    let file_path = relative_path(&file_path);
+    if let Some(err) = key_error() {
+        return err;
+    }
 
-    let data = match fs::read(file_path) {
-        Ok(data) => data,
-        Err(err) => panic!("Failed to read the file: {err}"),
+    let include = match read_include(tokens.into(), &["compress"]) {
+        Ok(include) => include,
+        Err(err) => return expr_error(err),
     };
+    let literal = LiteralBytes::ByteStr(include.data);
+    let encrypted = literal.encrypt_include(include.flags.contains(&"compress"));
+    let track = include.track;
 
-    LiteralBytes::ByteStr(data).encrypt().into()
+    quote! {{ #track #encrypted }}.into()
 }
 
 /// Encrypts a UTF-8 file as a string
+///
+/// Whatever its size, the file is decrypted by a `BooStrBlob` into a caller buffer.
 #[proc_macro]
 pub fn boo_include_str(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let Some(file_path) = read_literal_str(tokens) else {
-        panic!("{INCLUDE_ERROR}");
-    };
-    let file_path = relative_path(&file_path);
+    if let Some(err) = key_error() {
+        return err;
+    }
 
-    let data = match fs::read_to_string(file_path) {
+    let include = match read_include(tokens.into(), &["compress"]) {
+        Ok(include) => include,
+        Err(err) => return expr_error(err),
+    };
+    let data = match String::from_utf8(include.data) {
         Ok(data) => data,
-        Err(err) => panic!("Failed to read the file: {err}"),
+        Err(err) => {
+            return expr_error(syn::Error::new(
+                include.path.span(),
+                format!("`{}` is not UTF-8: {err}", include.path.value()),
+            ));
+        }
+    };
+
+    let literal = LiteralBytes::Str {
+        capacity: data.len(),
+        bytes: data.into_bytes(),
     };
+    let encrypted = literal.encrypt_include(include.flags.contains(&"compress"));
+    let track = include.track;
 
-    LiteralBytes::Str(data.into_bytes()).encrypt().into()
+    quote! {{ #track #encrypted }}.into()
 }
 
-/// Reads a single string literal from a token stream
+/// Encrypts a raw file as bytes, decrypted piece by piece by a `BooStream`
+#[proc_macro]
+pub fn boo_stream(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    let include = match read_include(tokens.into(), &[]) {
+        Ok(include) => include,
+        Err(err) => return expr_error(err),
+    };
+    let stream = LiteralBytes::ByteStr(include.data).encrypt_stream();
+    let track = include.track;
+
+    quote! {{ #track #stream }}.into()
+}
+
+/// Encrypts the files of a directory tree, looked up by path or iterated from a `BooDir`
+///
+/// Each file is decrypted piece by piece by a `BooStream`.
+/// With the `encrypt_names` flag, the paths are encrypted too: they can still be looked up,
+/// but `BooFile::name()` returns `None`.
+#[proc_macro]
+pub fn boo_include_dir(tokens: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    // Invalid flags are reported as is, spanning the flag
+    let parser = |input: ParseStream| {
+        let (path, dir_path) =
+            include_path(input).map_err(|err| syn::Error::new(err.span(), INCLUDE_DIR_ERROR))?;
+        let flags = include_flags(input, &["encrypt_names"])?;
+
+        Ok((path, dir_path, flags.contains(&"encrypt_names")))
+    };
+    let (path, dir_path, encrypt_names) = match parser.parse(tokens) {
+        Ok(args) => args,
+        Err(err) => return expr_error(err),
+    };
+
+    let read = || {
+        walk_dir(&dir_path)?
+            .into_iter()
+            .map(|(name, file_path)| Ok((name, fs::read(&file_path)?, file_path)))
+            .collect::<std::io::Result<Vec<_>>>()
+    };
+    let files = match read() {
+        Ok(files) => files,
+        Err(err) => {
+            return expr_error(syn::Error::new(
+                path.span(),
+                format!("couldn't read `{}`: {err}", dir_path.display()),
+            ));
+        }
+    };
+
+    let track = files
+        .iter()
+        .map(|(_, _, file_path)| track_file(file_path))
+        .collect::<Vec<_>>();
+    let files = files
+        .into_iter()
+        .map(|(name, data, _)| (name, data))
+        .collect();
+    let dir = encrypt_dir(files, encrypt_names);
+
+    quote! {{ #(#track)* #dir }}.into()
+}
+
+/// Expands to `mac` called with encrypted format string text
 ///
 /// # Arguments
 ///
-/// * `tokens` - Token stream containing a single string literal
-fn read_literal_str(tokens: proc_macro::TokenStream) -> Option<String> {
-    if let Ok(expr) = syn::parse2::<Expr>(tokens.into()) {
-        match expr {
-            Expr::Lit(ExprLit {
-                lit: Lit::Str(str), ..
-            }) => return Some(str.value()),
-            _ => {}
+/// * `tokens` - Arguments of the formatting macro
+/// * `mac` - Name of the formatting macro, resolved at the call site
+/// * `destination` - Whether the format string is preceded by a destination, like with `write!()`
+fn format_macro(
+    tokens: proc_macro::TokenStream,
+    mac: proc_macro2::TokenStream,
+    destination: bool,
+) -> proc_macro::TokenStream {
+    if let Some(err) = key_error() {
+        return err;
+    }
+
+    match encrypt_format(tokens.into(), destination) {
+        Ok(args) => quote! { #mac!(#args) }.into(),
+        Err(err) => expr_error(err),
+    }
+}
+
+/// Converts `err` into compile errors, usable where an expression is expected
+///
+/// Each error is a separate `compile_error!` invocation, grouped in a block to form a single expression.
+fn expr_error(err: syn::Error) -> proc_macro::TokenStream {
+    let errors = err.to_compile_error();
+
+    quote! {{ #errors }}.into()
+}
+
+/// File read by an include macro
+struct Include {
+    /// Content of the file
+    data: Vec<u8>,
+    /// Path literal, spanning the errors about the file
+    path: LitStr,
+    /// Tokens registering the file as a dependency
+    track: proc_macro2::TokenStream,
+    /// Flags following the path
+    flags: Vec<&'static str>,
+}
+
+/// Reads the file whose path is given to an include macro
+///
+/// The path is either a string literal relative to the calling source file, like with `include_bytes!()`,
+/// or a `manifest = "..."` string literal relative to the calling crate manifest directory.
+///
+/// # Arguments
+///
+/// * `tokens` - Token stream containing the path, optionally followed by flags
+/// * `supported_flags` - Flags accepted after the path, like `compress`
+///
+/// # Returns
+///
+/// The file read, or an error spanning the invalid tokens or the path literal
+fn read_include(
+    tokens: proc_macro2::TokenStream,
+    supported_flags: &[&'static str],
+) -> syn::Result<Include> {
+    let usage = || {
+        let flags = supported_flags.iter().map(|flag| format!("`, {flag}`"));
+        let message = match supported_flags.is_empty() {
+            true => INCLUDE_ERROR.to_string(),
+            false => format!(
+                "{INCLUDE_ERROR}, optionally followed by {}",
+                flags.collect::<Vec<_>>().join(" or ")
+            ),
+        };
+        syn::Error::new_spanned(&tokens, message)
+    };
+    // Invalid flags are reported as is, spanning the flag
+    let parser = |input: ParseStream| {
+        let (path, file_path) = include_path(input).map_err(|_| usage())?;
+        let flags = include_flags(input, supported_flags)?;
+
+        Ok((path, file_path, flags))
+    };
+
+    let (path, file_path, flags) = parser.parse2(tokens.clone())?;
+
+    match fs::read(&file_path) {
+        Ok(data) => Ok(Include {
+            data,
+            path,
+            track: track_file(&file_path),
+            flags,
+        }),
+        Err(err) => Err(syn::Error::new(
+            path.span(),
+            format!("couldn't read `{}`: {err}", file_path.display()),
+        )),
+    }
+}
+
+/// Parses the path given to an include macro, resolved from the calling source file
+/// or from the calling crate manifest directory with `manifest = "..."`
+///
+/// # Returns
+///
+/// The path literal and the resolved path
+fn include_path(input: ParseStream) -> syn::Result<(LitStr, PathBuf)> {
+    let manifest = input.peek(Ident) && input.peek2(Token![=]);
+    if manifest {
+        let ident = input.parse::<Ident>()?;
+        if ident != "manifest" {
+            return Err(syn::Error::new(ident.span(), INCLUDE_ERROR));
         }
+        input.parse::<Token![=]>()?;
+    }
+    let path = input.parse::<LitStr>()?;
+
+    let resolved = match manifest {
+        true => manifest_path(&path.value()),
+        false => relative_path(&path.value()),
     };
 
-    None
+    Ok((path, resolved))
+}
+
+/// Parses the flags following the path of an include macro: `, flag_a, flag_b`
+///
+/// # Returns
+///
+/// The flags, or an error spanning the first one not in `supported`
+fn include_flags(input: ParseStream, supported: &[&'static str]) -> syn::Result<Vec<&'static str>> {
+    let mut flags = Vec::new();
+    while input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+        let flag = input.parse::<Ident>()?;
+        match supported.iter().find(|supported| flag == **supported) {
+            Some(supported) => flags.push(*supported),
+            None => return Err(syn::Error::new(flag.span(), "unsupported flag")),
+        }
+    }
+
+    Ok(flags)
 }
 
-/// Makes a path relative to the calling source code file
+/// Registers a file read by a macro as a dependency of the calling crate, so that cargo rebuilds it
+/// when the file changes
+///
+/// The file is included in an unused constant, which never reaches the binary.
+fn track_file(path: &Path) -> proc_macro2::TokenStream {
+    // `include_bytes!()` resolves relative paths from the calling source file
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let path = path.to_string_lossy().into_owned();
+
+    quote! { const _: &[u8] = include_bytes!(#path); }
+}
+
+/// Makes a path relative to the directory of the source file calling the macro
+///
+/// Falls back to the calling crate manifest directory when the source file is unknown,
+/// like for code generated by another macro.
 fn relative_path(path: &str) -> PathBuf {
-    let current_dir = Path::new(file!())
-        .parent()
-        .unwrap_or_else(|| Path::new("."));
+    let source_dir = proc_macro::is_available()
+        .then(|| proc_macro::Span::call_site().local_file())
+        .flatten()
+        .and_then(|file| file.parent().map(Path::to_path_buf));
 
-    current_dir.join(path)
+    match source_dir {
+        Some(source_dir) => source_dir.join(path),
+        None => manifest_path(path),
+    }
 }
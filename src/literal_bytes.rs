@@ -1,34 +1,70 @@
 use std::borrow::Cow;
 
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{Expr, ExprArray, ExprTuple, ExprUnary, Lit, UnOp};
+use proc_macro2::{Literal, TokenStream};
+use quote::{ToTokens, quote};
+use syn::{Expr, ExprArray, ExprTuple, ExprUnary, Lit, LitStr, Pat, Type, UnOp};
 
-use crate::utils::xor;
-use crate::KEY;
+use crate::cipher::Cipher;
+use crate::compress::lz4_compress;
+use crate::number::{self, FloatType, IntType};
+use crate::random::Random;
 
 /// Global error message with usage hints
 const GLOBAL_ERROR: &str = r#"expected one literal parameter (like `true`, `1`, `1.14`, `'d'`, `b'e'`, `"foo"`, `b"bar"`, `c"baz"`, `["1", "2", "3"]`, `(99, "20", [3.3, 4.5])`)"#;
 
+/// Wide string error message
+const WIDE_ERROR: &str = r#"expected one string literal (like `"foo"`)"#;
+
+/// Constant-time comparison error message
+const EQ_ERROR: &str = r#"expected a string or a byte string literal (like `"foo"` or `b"bar"`)"#;
+
+/// Size in bytes from which the ciphertext is emitted as a single byte string instead of one token per byte
+///
+/// Byte strings are compiled much faster than large arrays, but stored as a contiguous blob
+/// instead of being spread in the instructions.
+pub const BLOB_THRESHOLD: usize = 256;
+
 /// Bytes representation of a Rust literal
 pub enum LiteralBytes {
     /// A UTF-8 string literal: `"foo"`.
-    Str(Vec<u8>),
+    ///
+    /// Padded with zeros up to `capacity`, shared by all strings at the same position of an array.
+    Str { bytes: Vec<u8>, capacity: usize },
 
     /// A byte string literal: `b"foo"`.
     ByteStr(Vec<u8>),
 
     /// A null-terminated C-string literal: `c"foo"`.
-    CStr(Vec<u8>),
+    ///
+    /// Padded with zeros up to `capacity`, like [`LiteralBytes::Str`].
+    CStr { bytes: Vec<u8>, capacity: usize },
+
+    /// A null-terminated UTF-16 string, as little endian bytes: `boo_wide!("foo")`.
+    WideStr(Vec<u8>),
 
     /// A byte literal: `b'a'`.
     Byte(Vec<u8>),
 
-    /// An int literal: `1isize`.
-    Int(Vec<u8>),
+    /// An int literal: `1isize`, `1u8` or `1`.
+    ///
+    /// Without suffix, the type is inferred at the call site, the literal `expr` being kept to check it.
+    Int {
+        bytes: Vec<u8>,
+        ty: Option<&'static IntType>,
+        magnitude: u128,
+        negative: bool,
+        expr: TokenStream,
+    },
 
-    /// A float literal: `0f64`.
-    Float(Vec<u8>),
+    /// A float literal: `0f64`, `0f32` or `0.`.
+    ///
+    /// Without suffix, the type is inferred at the call site, the literal `expr` being kept to check it.
+    Float {
+        bytes: Vec<u8>,
+        ty: Option<&'static FloatType>,
+        f32_overflow: bool,
+        expr: TokenStream,
+    },
 
     /// A character literal: `'a'`.
     Char(Vec<u8>),
@@ -57,26 +93,45 @@ impl LiteralBytes {
     /// # Arguments
     ///
     /// * `tokens` - Input token stream representing a Rust literal
-    pub fn parse(tokens: TokenStream) -> Result<Self, &'static str> {
-        let Ok(expr) = syn::parse2::<Expr>(tokens) else {
-            return Err(GLOBAL_ERROR);
+    pub fn parse(tokens: TokenStream) -> syn::Result<Self> {
+        let Ok(expr) = syn::parse2::<Expr>(tokens.clone()) else {
+            return Err(syn::Error::new_spanned(tokens, GLOBAL_ERROR));
         };
+        let error = || syn::Error::new_spanned(&expr, GLOBAL_ERROR);
 
         if let Expr::Array(ExprArray { elems, .. }) | Expr::Tuple(ExprTuple { elems, .. }) = &expr {
-            let inner = elems
-                .iter()
-                .map(Expr::to_token_stream)
-                .map(Self::parse)
-                .collect::<Result<Vec<_>, _>>()?;
-
-            return Ok(Self::Sequence {
-                inner,
-                delimiter: match &expr {
-                    Expr::Array(_) => Delimiter::Bracket,
-                    Expr::Tuple(_) => Delimiter::Parenthesis,
-                    _ => unreachable!(),
-                },
-            });
+            let delimiter = match &expr {
+                Expr::Array(_) => Delimiter::Bracket,
+                _ => Delimiter::Parenthesis,
+            };
+
+            // All the invalid items are reported, not only the first one
+            let mut inner = Vec::with_capacity(elems.len());
+            let mut errors: Option<syn::Error> = None;
+            for elem in elems {
+                let item = Self::parse(elem.to_token_stream()).and_then(|item| {
+                    match (&delimiter, inner.first()) {
+                        (Delimiter::Bracket, Some(first)) if !item.same_kind(first) => Err(
+                            syn::Error::new_spanned(elem, "array items must share the same type"),
+                        ),
+                        _ => Ok(item),
+                    }
+                });
+
+                match (item, &mut errors) {
+                    (Ok(item), _) => inner.push(item),
+                    (Err(err), Some(errors)) => errors.combine(err),
+                    (Err(err), None) => errors = Some(err),
+                }
+            }
+            if let Some(errors) = errors {
+                return Err(errors);
+            }
+
+            let mut sequence = Self::Sequence { inner, delimiter };
+            sequence.unify_capacities();
+
+            return Ok(sequence);
         }
 
         let (lit, negative) = match &expr {
@@ -87,75 +142,210 @@ impl LiteralBytes {
                 ..
             }) => {
                 let Expr::Lit(lit) = expr.as_ref() else {
-                    return Err(GLOBAL_ERROR);
+                    return Err(error());
                 };
                 if !matches!(lit.lit, Lit::Int(_) | Lit::Float(_)) {
-                    return Err(GLOBAL_ERROR);
+                    return Err(error());
                 }
 
                 (lit, true)
             }
-            _ => return Err(GLOBAL_ERROR),
+            _ => return Err(error()),
         };
 
         let literal = match &lit.lit {
-            Lit::Str(s) => Self::Str(s.value().into_bytes()),
+            Lit::Str(s) => {
+                let bytes = s.value().into_bytes();
+
+                Self::Str {
+                    capacity: bytes.len(),
+                    bytes,
+                }
+            }
             Lit::ByteStr(s) => Self::ByteStr(s.value()),
-            Lit::CStr(s) => Self::CStr(s.value().into_bytes_with_nul()),
+            Lit::CStr(s) => {
+                let bytes = s.value().into_bytes_with_nul();
+
+                Self::CStr {
+                    capacity: bytes.len(),
+                    bytes,
+                }
+            }
             Lit::Byte(b) => Self::Byte(vec![b.value()]),
-            Lit::Char(c) => Self::Char((c.value() as u32).to_ne_bytes().to_vec()),
+            Lit::Char(c) => Self::Char((c.value() as u32).to_le_bytes().to_vec()),
             Lit::Int(i) => {
-                const ERROR: &str = "only `isize` integer literals supported";
-
-                let int = if negative {
-                    let Ok(int) = i.base10_parse::<usize>() else {
-                        return Err(ERROR);
-                    };
-
-                    // cast isize::MIN to usize to remove the sign
-                    if int > isize::MIN as usize {
-                        return Err(ERROR);
-                    }
-
-                    // two complement
-                    let signed = (!int).wrapping_add(1) as isize;
-
-                    signed
-                } else if let Ok(int) = i.base10_parse::<isize>() {
-                    int
-                } else {
-                    return Err(ERROR);
+                let ty = match i.suffix() {
+                    "" => None,
+                    suffix => Some(IntType::find(suffix).ok_or_else(|| {
+                        syn::Error::new(i.span(), format!("unsupported integer suffix `{suffix}`"))
+                    })?),
+                };
+                let out_of_range = || {
+                    let name = ty.map_or("any integer type", |ty| ty.name);
+                    syn::Error::new_spanned(
+                        &expr,
+                        format!("integer literal is out of range for `{name}`"),
+                    )
                 };
 
-                Self::Int(int.to_ne_bytes().to_vec())
+                let magnitude = i.base10_parse::<u128>().map_err(|_| out_of_range())?;
+                let bytes = match ty {
+                    Some(ty) => ty.encode(magnitude, negative),
+                    None => number::encode_inferred_int(magnitude, negative),
+                }
+                .ok_or_else(out_of_range)?;
+
+                Self::Int {
+                    bytes,
+                    ty,
+                    magnitude,
+                    negative,
+                    expr: expr.to_token_stream(),
+                }
             }
             Lit::Float(f) => {
-                let Ok(mut float) = f.base10_parse::<f64>() else {
-                    return Err("only `f64` float literals supported");
+                let ty = match f.suffix() {
+                    "" => None,
+                    suffix => Some(FloatType::find(suffix).ok_or_else(|| {
+                        syn::Error::new(f.span(), format!("unsupported float suffix `{suffix}`"))
+                    })?),
                 };
 
-                if negative {
-                    float = -float;
-                }
+                let encoded = match ty {
+                    Some(ty) => ty
+                        .encode(f.base10_digits(), negative)
+                        .map(|bytes| (bytes, false)),
+                    None => number::encode_inferred_float(f.base10_digits(), negative),
+                };
+                let Some((bytes, f32_overflow)) = encoded else {
+                    let name = ty.map_or("f64", |ty| ty.name);
+                    return Err(syn::Error::new_spanned(
+                        &expr,
+                        format!("float literal is out of range for `{name}`"),
+                    ));
+                };
 
-                Self::Float(float.to_ne_bytes().to_vec())
+                Self::Float {
+                    bytes,
+                    ty,
+                    f32_overflow,
+                    expr: expr.to_token_stream(),
+                }
             }
             Lit::Bool(b) => Self::Bool(vec![b.value as u8]),
-            _ => return Err(GLOBAL_ERROR),
+            _ => return Err(error()),
         };
 
         Ok(literal)
     }
 
+    /// Parses a token stream containing a single string literal into a [`LiteralBytes::WideStr`]
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Input token stream representing a Rust string literal
+    pub fn parse_wide(tokens: TokenStream) -> syn::Result<Self> {
+        let Ok(str) = syn::parse2::<LitStr>(tokens.clone()) else {
+            return Err(syn::Error::new_spanned(tokens, WIDE_ERROR));
+        };
+
+        let bytes = str
+            .value()
+            .encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect();
+
+        Ok(Self::WideStr(bytes))
+    }
+
+    /// Checks if two literals are of the same kind, as required by array items
+    ///
+    /// Integers and floats of different types are accepted, their types being checked by the compiler.
+    fn same_kind(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Sequence {
+                    inner, delimiter, ..
+                },
+                Self::Sequence {
+                    inner: other_inner,
+                    delimiter: other_delimiter,
+                },
+            ) => {
+                matches!(
+                    (delimiter, other_delimiter),
+                    (Delimiter::Bracket, Delimiter::Bracket)
+                        | (Delimiter::Parenthesis, Delimiter::Parenthesis)
+                ) && inner.len() == other_inner.len()
+                    && inner
+                        .iter()
+                        .zip(other_inner)
+                        .all(|(item, other)| item.same_kind(other))
+            }
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+
+    /// Gives the same capacity to the (C-)strings of an array items, so that they share the same type
+    ///
+    /// Items are expected to be unified already.
+    fn unify_capacities(&mut self) {
+        let Self::Sequence {
+            inner,
+            delimiter: Delimiter::Bracket,
+        } = self
+        else {
+            return;
+        };
+        let Some((first, others)) = inner.split_first_mut() else {
+            return;
+        };
+
+        // Widen the first item to the largest capacities, then the others to the first one
+        for other in others.iter() {
+            first.widen(other);
+        }
+        for other in others {
+            other.widen(first);
+        }
+    }
+
+    /// Widens the (C-)strings capacities to the ones of the same position in `other`
+    fn widen(&mut self, other: &Self) {
+        match (self, other) {
+            (
+                Self::Str { capacity, .. },
+                Self::Str {
+                    capacity: other_capacity,
+                    ..
+                },
+            )
+            | (
+                Self::CStr { capacity, .. },
+                Self::CStr {
+                    capacity: other_capacity,
+                    ..
+                },
+            ) => *capacity = (*capacity).max(*other_capacity),
+            (Self::Sequence { inner, .. }, Self::Sequence { inner: other, .. }) => {
+                for (item, other) in inner.iter_mut().zip(other) {
+                    item.widen(other);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// Gets the length of the literal's byte representation
     pub fn len(&self) -> usize {
         match self {
-            Self::Str(v)
-            | Self::ByteStr(v)
-            | Self::CStr(v)
+            Self::Str { capacity, .. } | Self::CStr { capacity, .. } => *capacity,
+            Self::ByteStr(v)
+            | Self::WideStr(v)
             | Self::Byte(v)
-            | Self::Int(v)
-            | Self::Float(v)
+            | Self::Int { bytes: v, .. }
+            | Self::Float { bytes: v, .. }
             | Self::Char(v)
             | Self::Bool(v) => v.len(),
             Self::Sequence { inner, .. } => inner.iter().map(Self::len).sum(),
@@ -169,12 +359,17 @@ impl LiteralBytes {
     /// Borrowed or owned byte representation
     pub fn as_bytes(&self) -> Cow<'_, [u8]> {
         match self {
-            Self::Str(v)
-            | Self::ByteStr(v)
-            | Self::CStr(v)
+            Self::Str { bytes, capacity } | Self::CStr { bytes, capacity } => {
+                let mut bytes = bytes.clone();
+                bytes.resize(*capacity, 0);
+
+                bytes.into()
+            }
+            Self::ByteStr(v)
+            | Self::WideStr(v)
             | Self::Byte(v)
-            | Self::Int(v)
-            | Self::Float(v)
+            | Self::Int { bytes: v, .. }
+            | Self::Float { bytes: v, .. }
             | Self::Char(v)
             | Self::Bool(v) => v.into(),
             Self::Sequence { inner, .. } => inner
@@ -189,19 +384,215 @@ impl LiteralBytes {
 
 impl LiteralBytes {
     /// Encrypts itself inside a runtime decryption code.
+    ///
+    /// Each call draws a new nonce, so identical literals never share the same ciphertext,
+    /// and a new salt deriving the subkey of this literal only.
     pub fn encrypt(self) -> TokenStream {
-        let mut bytes = self.as_bytes().to_vec();
-        xor(&mut bytes, &KEY);
-
-        let decrypted = quote! {
-            let mut bytes = {
-                let mut bytes = [#(#bytes),*];
-                crate::__boo::xor(&mut bytes, crate::BOO_KEY);
-                bytes
-            };
+        let bytes = self.as_bytes().to_vec();
+        let len = bytes.len();
+        let decrypted = Self::encrypt_bytes(bytes);
+
+        unsafe { Self::decrypt(self, decrypted, len) }
+    }
+
+    /// Encrypts `bytes` with a new salt and nonce
+    ///
+    /// # Returns
+    ///
+    /// The statements decrypting them into a `bytes: [u8; N]` binding
+    fn encrypt_bytes(mut bytes: Vec<u8>) -> TokenStream {
+        let cipher = Cipher::SELECTED;
+        let mut random = Random::call_site();
+        let salt = random.array();
+        let nonce = random.array();
+        cipher.encrypt(&mut bytes, &salt, &nonce);
+        let decrypt = cipher.decrypt(&salt, &nonce, &mut random);
+
+        let ciphertext = match bytes.len() < BLOB_THRESHOLD {
+            true => quote! { [#(#bytes),*] },
+            false => {
+                let blob = Literal::byte_string(&bytes);
+                quote! { *#blob }
+            }
         };
 
-        unsafe { Self::decrypt(self, decrypted, bytes.len()) }
+        quote! {
+            let mut bytes = #ciphertext;
+            #decrypt
+        }
+    }
+
+    /// Encrypts an included file, decrypted into a caller buffer
+    ///
+    /// Whatever its size, the file is kept encrypted in a static by a `BooBlob`, or a `BooStrBlob` for
+    /// strings, never copied to the stack.
+    ///
+    /// # Arguments
+    ///
+    /// * `compress` - Whether the file is compressed with LZ4 before being encrypted
+    pub fn encrypt_include(self, compress: bool) -> TokenStream {
+        let bytes = self.as_bytes();
+        let len = bytes.len();
+        let blob = match compress {
+            true => {
+                let parts = Self::encrypt_bytes_parts(lz4_compress(&bytes));
+                quote! { crate::__boo::BooBlob::compressed(#parts, #len) }
+            }
+            false => {
+                let parts = Self::encrypt_bytes_parts(bytes.into_owned());
+                quote! { crate::__boo::BooBlob::new(#parts) }
+            }
+        };
+
+        match self {
+            // Safety: included strings are checked to be UTF-8 at compile time
+            Self::Str { .. } => quote! { unsafe { crate::__boo::BooStrBlob::new(#blob) } },
+            _ => blob,
+        }
+    }
+
+    /// Encrypts itself inside a `BooStatic`, decrypted on first access
+    ///
+    /// # Returns
+    ///
+    /// The type of the static and its initializer
+    pub fn encrypt_static(self, declared: &Type) -> (TokenStream, TokenStream) {
+        let value_type = self.value_type(declared);
+        let value = self.encrypt();
+
+        (
+            quote! { crate::__boo::BooStatic<#value_type> },
+            quote! { crate::__boo::BooStatic::new(|| #value) },
+        )
+    }
+
+    /// Gets the type of the decrypted value, given the type `declared` for it
+    ///
+    /// Strings are decrypted into their stack types, other literals into the declared type.
+    fn value_type(&self, declared: &Type) -> TokenStream {
+        match (self, declared) {
+            (Self::Str { capacity, .. }, _) => quote! { crate::__boo::BooStr<#capacity> },
+            (Self::CStr { capacity, .. }, _) => quote! { crate::__boo::BooCStr<#capacity> },
+            (Self::ByteStr(bytes), _) => {
+                let len = bytes.len();
+                quote! { [u8; #len] }
+            }
+            (Self::WideStr(bytes), _) => {
+                let units = bytes.len() / 2;
+                quote! { crate::__boo::BooWideStr<#units> }
+            }
+            (
+                Self::Sequence {
+                    inner,
+                    delimiter: Delimiter::Bracket,
+                },
+                Type::Array(array),
+            ) if !inner.is_empty() => {
+                // Items are unified, sharing the same type
+                let item = inner[0].value_type(&array.elem);
+                let len = inner.len();
+                quote! { [#item; #len] }
+            }
+            (
+                Self::Sequence {
+                    inner,
+                    delimiter: Delimiter::Parenthesis,
+                },
+                Type::Tuple(tuple),
+            ) if inner.len() == tuple.elems.len() => {
+                let items = inner
+                    .iter()
+                    .zip(&tuple.elems)
+                    .map(|(literal, declared)| literal.value_type(declared));
+                quote! { (#(#items,)*) }
+            }
+            (_, Type::Paren(paren)) => self.value_type(&paren.elem),
+            _ => declared.to_token_stream(),
+        }
+    }
+
+    /// Encrypts itself inside a `BooStream`, decrypting its bytes piece by piece
+    pub fn encrypt_stream(self) -> TokenStream {
+        let parts = self.encrypt_parts();
+
+        quote! {
+            crate::__boo::BooStream::new(#parts)
+        }
+    }
+
+    /// Encrypts its bytes for a runtime type decrypting them piece by piece, like `BooStream`
+    ///
+    /// # Returns
+    ///
+    /// The `ciphertext, shares, nonce` arguments of the runtime type constructor
+    pub fn encrypt_parts(self) -> TokenStream {
+        Self::encrypt_bytes_parts(self.as_bytes().to_vec())
+    }
+
+    /// Encrypts `bytes` with a new salt and nonce, like [`LiteralBytes::encrypt_parts`]
+    fn encrypt_bytes_parts(mut bytes: Vec<u8>) -> TokenStream {
+        let cipher = Cipher::SELECTED;
+        let mut random = Random::call_site();
+        let salt = random.array();
+        let nonce = random.array();
+        cipher.encrypt(&mut bytes, &salt, &nonce);
+
+        let ciphertext = Literal::byte_string(&bytes);
+        let shares = cipher.subkey_shares(&salt, &mut random);
+        let nonce = Literal::byte_string(&nonce);
+
+        quote! { #ciphertext, #shares, *#nonce }
+    }
+
+    /// Encrypts itself inside a runtime code comparing it with `input` in constant time
+    ///
+    /// `input` can be any `AsRef<[u8]>` value, compared with the bytes of the literal.
+    ///
+    /// # Returns
+    ///
+    /// An error if the literal is neither a string nor a byte string
+    pub fn encrypt_eq(self, input: &Expr) -> Result<TokenStream, &'static str> {
+        let mut bytes = match self {
+            Self::Str { bytes, .. } | Self::ByteStr(bytes) => bytes,
+            _ => return Err(EQ_ERROR),
+        };
+
+        let cipher = Cipher::SELECTED;
+        let mut random = Random::call_site();
+        let salt = random.array();
+        let nonce = random.array();
+        cipher.encrypt(&mut bytes, &salt, &nonce);
+        let compare = cipher.compare(&bytes, &salt, &nonce, &mut random);
+
+        Ok(quote! {{
+            let input = &#input;
+            let input: &[u8] = ::core::convert::AsRef::<[u8]>::as_ref(input);
+            #compare
+        }})
+    }
+
+    /// Encrypts itself inside a runtime decryption code lending the decrypted value to `closure`
+    ///
+    /// The value is wiped as soon as the closure returns or panics, so it cannot outlive the call.
+    pub fn encrypt_with(self, closure: &Expr) -> TokenStream {
+        let value = self.encrypt();
+
+        // A typed parameter like `&str` relies on deref coercion, only applied by direct calls
+        let untyped = matches!(
+            closure,
+            Expr::Closure(closure) if closure.inputs.iter().any(|input| !matches!(input, Pat::Type(_)))
+        );
+        let call = if untyped {
+            quote! { crate::__boo::lend(&value.0, #closure) }
+        } else {
+            quote! { (#closure)(&value.0) }
+        };
+
+        quote! {{
+            // Safety: all literal types, including the decrypted strings, are valid when zeroed
+            let value = unsafe { crate::__boo::Wiped::new(#value) };
+            #call
+        }}
     }
 
     /// Decrypts a `LiteralBytes` into its original form by interpreting the decrypted byte buffer.
@@ -222,8 +613,8 @@ impl LiteralBytes {
     ///
     /// # Memory Model
     ///
-    /// - String types are heap-allocated.
-    /// - Other types are reinterpreted directly from the stack.
+    /// - All types, including strings, are reinterpreted directly from the stack.
+    /// - Decrypted bytes are wiped once converted, and strings wipe themselves when dropped.
     ///
     /// # Performance
     ///
@@ -234,45 +625,88 @@ impl LiteralBytes {
         mut decrypted_len: usize,
     ) -> TokenStream {
         match literal {
-            LiteralBytes::Str(_) => quote! {
-                unsafe {
-                    #decrypted
-                    ::alloc::string::String::from_utf8_unchecked(bytes.to_vec())
+            LiteralBytes::Str { bytes, .. } => {
+                let len = bytes.len();
+
+                quote! {
+                    unsafe {
+                        #decrypted
+                        crate::__boo::BooStr::from_utf8_unchecked(crate::__boo::take(&mut bytes), #len)
+                    }
                 }
-            },
+            }
             LiteralBytes::ByteStr(_) => quote! {{
                 #decrypted
-                bytes
+                crate::__boo::take(&mut bytes)
             }},
-            LiteralBytes::CStr(_) => quote! {
-                unsafe {
-                    #decrypted
-                    ::alloc::ffi::CString::from_vec_with_nul_unchecked(bytes.to_vec())
+            LiteralBytes::CStr { bytes, .. } => {
+                let len = bytes.len();
+
+                quote! {
+                    unsafe {
+                        #decrypted
+                        crate::__boo::BooCStr::from_bytes_with_nul_unchecked(
+                            crate::__boo::take(&mut bytes),
+                            #len,
+                        )
+                    }
                 }
-            },
+            }
+            LiteralBytes::WideStr(bytes) => {
+                let units = bytes.len() / 2;
+                quote! {{
+                    #decrypted
+                    crate::__boo::BooWideStr::<#units>::from_le_bytes(crate::__boo::take(&mut bytes))
+                }}
+            }
             LiteralBytes::Byte(_) => quote! {{
                 #decrypted
                 // Extra compile time type assertion
-                (bytes as [u8; 1])[0]
-            }},
-            LiteralBytes::Int(_) => quote! {{
-                #decrypted
-                isize::from_ne_bytes(bytes)
-            }},
-            LiteralBytes::Float(_) => quote! {{
-                #decrypted
-                f64::from_ne_bytes(bytes)
+                (crate::__boo::take(&mut bytes) as [u8; 1])[0]
             }},
+            LiteralBytes::Int {
+                ty,
+                magnitude,
+                negative,
+                expr,
+                ..
+            } => {
+                let int = match ty {
+                    Some(ty) => ty.decode(magnitude, negative),
+                    None => number::decode_inferred_int(magnitude, negative, expr),
+                };
+
+                quote! {{
+                    #decrypted
+                    #int
+                }}
+            }
+            LiteralBytes::Float {
+                ty,
+                f32_overflow,
+                expr,
+                ..
+            } => {
+                let float = match ty {
+                    Some(ty) => ty.decode(),
+                    None => number::decode_inferred_float(f32_overflow, expr),
+                };
+
+                quote! {{
+                    #decrypted
+                    #float
+                }}
+            }
             LiteralBytes::Char(_) => quote! {
                 unsafe {
                     #decrypted
-                    ::core::char::from_u32_unchecked(u32::from_ne_bytes(bytes))
+                    ::core::char::from_u32_unchecked(u32::from_le_bytes(crate::__boo::take(&mut bytes)))
                 }
             },
             LiteralBytes::Bool(_) => quote! {{
                 #decrypted
                 // Extra compile time type assertion
-                (bytes as [u8; 1])[0] != 0
+                (crate::__boo::take(&mut bytes) as [u8; 1])[0] != 0
             }},
             LiteralBytes::Sequence { inner, delimiter } => {
                 let (bytes, items): (Vec<_>, Vec<_>) = inner
@@ -286,14 +720,15 @@ impl LiteralBytes {
                             .expect("Sequence item exceeds remaining bytes");
 
                         let bytes = quote! {
-                            let (#item_var, bytes) = crate::__boo::split_array::<
+                            let (mut #item_var, mut bytes) = crate::__boo::split_array::<
                                 #decrypted_len,
                                 #item_len,
                                 #remaining_len
-                            >(bytes);
+                            >(crate::__boo::take(&mut bytes));
                         };
 
-                        let item_bytes = quote! { let mut bytes = #item_var; };
+                        let item_bytes =
+                            quote! { let mut bytes = crate::__boo::take(&mut #item_var); };
                         let decrypted_item =
                             unsafe { Self::decrypt(literal, item_bytes, item_len) };
 
@@ -0,0 +1,66 @@
+//! # Warning
+//!
+//! This module is imported as is in the proc macro.
+//! Like `utils.rs`, it must not depend on anything outside `core`.
+//!
+//! Decompression of [LZ4 blocks](https://github.com/lz4/lz4/blob/dev/doc/lz4_Block_format.md),
+//! produced at compile time by the proc macro.
+
+/// Decompresses the LZ4 block read from `source` into `destination`, which must fit the decompressed bytes.
+///
+/// The block is read byte by byte, so that it can be decrypted on the fly by a `BooStream`.
+/// Matches are copied byte by byte too, so that they can overlap the bytes they produce.
+///
+/// # Returns
+///
+/// The number of bytes written into `destination`
+#[allow(unused)]
+pub fn lz4_decompress(source: impl IntoIterator<Item = u8>, destination: &mut [u8]) -> usize {
+    let mut source = source.into_iter().peekable();
+    let mut written = 0;
+
+    while let Some(token) = source.next() {
+        let literals = lz4_length(&mut source, (token >> 4) as usize);
+        for byte in destination[written..written + literals].iter_mut() {
+            *byte = source.next().unwrap_or_default();
+        }
+        written += literals;
+
+        // The last sequence only has literals
+        if source.peek().is_none() {
+            break;
+        }
+
+        let offset = u16::from_le_bytes([
+            source.next().unwrap_or_default(),
+            source.next().unwrap_or_default(),
+        ]) as usize;
+        let len = lz4_length(&mut source, (token & 0x0F) as usize) + 4;
+        let start = written - offset;
+        let mut j = 0;
+        while j < len {
+            destination[written + j] = destination[start + j];
+            j += 1;
+        }
+        written += len;
+    }
+
+    written
+}
+
+/// Reads a sequence length of `len` from a 4-bit token field, followed in `source` by extra bytes when
+/// saturated.
+#[allow(unused)]
+#[inline(always)]
+fn lz4_length(source: &mut impl Iterator<Item = u8>, mut len: usize) -> usize {
+    if len == 0x0F {
+        for byte in source.by_ref() {
+            len += byte as usize;
+            if byte != 0xFF {
+                break;
+            }
+        }
+    }
+
+    len
+}
@@ -0,0 +1,216 @@
+use proc_macro2::{Delimiter, Group, TokenStream};
+use quote::{format_ident, quote};
+
+/// Integer type of a literal, selected by its suffix
+pub struct IntType {
+    /// Type name, also used as literal suffix
+    pub name: &'static str,
+    /// Size in bits of the encrypted representation
+    pub bits: u32,
+    /// Whether the type accepts negative values
+    pub signed: bool,
+}
+
+/// Integer types supported by literals
+///
+/// `isize` and `usize` are encrypted as 64 bits integers, their width depending on the target.
+pub const INT_TYPES: [IntType; 12] = [
+    IntType::new("u8", 8, false),
+    IntType::new("u16", 16, false),
+    IntType::new("u32", 32, false),
+    IntType::new("u64", 64, false),
+    IntType::new("u128", 128, false),
+    IntType::new("usize", 64, false),
+    IntType::new("i8", 8, true),
+    IntType::new("i16", 16, true),
+    IntType::new("i32", 32, true),
+    IntType::new("i64", 64, true),
+    IntType::new("i128", 128, true),
+    IntType::new("isize", 64, true),
+];
+
+impl IntType {
+    const fn new(name: &'static str, bits: u32, signed: bool) -> Self {
+        Self { name, bits, signed }
+    }
+
+    /// Finds the integer type named `name`
+    pub fn find(name: &str) -> Option<&'static Self> {
+        INT_TYPES.iter().find(|ty| ty.name == name)
+    }
+
+    /// Gets the little endian bytes of an integer, given its absolute value and sign
+    ///
+    /// # Returns
+    ///
+    /// `None` if the value is out of the type range
+    pub fn encode(&self, magnitude: u128, negative: bool) -> Option<Vec<u8>> {
+        if bits_needed(magnitude, negative, self.signed)? > self.bits {
+            return None;
+        }
+
+        let value = if negative {
+            magnitude.wrapping_neg()
+        } else {
+            magnitude
+        };
+
+        Some(truncate_le_bytes(value, self.bits))
+    }
+
+    /// Generates the expression converting a `bytes: [u8; N]` binding into the integer, wiping the binding
+    pub fn decode(&self, magnitude: u128, negative: bool) -> TokenStream {
+        let ty = format_ident!("{}", self.name);
+        let repr = format_ident!("{}{}", if self.signed { 'i' } else { 'u' }, self.bits);
+
+        if ty == repr {
+            return quote! { #ty::from_le_bytes(crate::__boo::take(&mut bytes)) };
+        }
+
+        // Pointer sized integers must fit the target width
+        let check = bits_needed(magnitude, negative, self.signed)
+            .filter(|bits| *bits > 16)
+            .map(|bits| {
+                let message = format!("integer literal is out of range for `{}`", self.name);
+                quote! { const { assert!(#ty::BITS >= #bits, #message) }; }
+            });
+
+        quote! {{
+            #check
+            #repr::from_le_bytes(crate::__boo::take(&mut bytes)) as #ty
+        }}
+    }
+}
+
+/// Float type of a literal, selected by its suffix
+pub struct FloatType {
+    /// Type name, also used as literal suffix
+    pub name: &'static str,
+}
+
+/// Float types supported by literals
+pub const FLOAT_TYPES: [FloatType; 2] = [FloatType { name: "f32" }, FloatType { name: "f64" }];
+
+impl FloatType {
+    /// Finds the float type named `name`
+    pub fn find(name: &str) -> Option<&'static Self> {
+        FLOAT_TYPES.iter().find(|ty| ty.name == name)
+    }
+
+    /// Gets the little endian bytes of a float from its decimal representation
+    ///
+    /// # Returns
+    ///
+    /// `None` if the value is out of the type range
+    pub fn encode(&self, digits: &str, negative: bool) -> Option<Vec<u8>> {
+        let sign = if negative { "-" } else { "" };
+        let digits = format!("{sign}{digits}");
+
+        match self.name {
+            "f32" => digits
+                .parse::<f32>()
+                .ok()
+                .filter(|float| float.is_finite())
+                .map(|float| float.to_le_bytes().to_vec()),
+            _ => digits
+                .parse::<f64>()
+                .ok()
+                .filter(|float| float.is_finite())
+                .map(|float| float.to_le_bytes().to_vec()),
+        }
+    }
+
+    /// Generates the expression converting a `bytes: [u8; N]` binding into the float, wiping the binding
+    pub fn decode(&self) -> TokenStream {
+        let ty = format_ident!("{}", self.name);
+
+        quote! { #ty::from_le_bytes(crate::__boo::take(&mut bytes)) }
+    }
+}
+
+/// Gets the little endian bytes of an unsuffixed integer, given its absolute value and sign
+///
+/// The integer is stored as a 128 bits two's complement, truncated once its type is inferred.
+///
+/// # Returns
+///
+/// `None` if the value is out of the `i128` and `u128` ranges
+pub fn encode_inferred_int(magnitude: u128, negative: bool) -> Option<Vec<u8>> {
+    // Negative values must fit `i128`, positive ones `u128`
+    if bits_needed(magnitude, negative, negative)? > u128::BITS {
+        return None;
+    }
+
+    let value = if negative {
+        magnitude.wrapping_neg()
+    } else {
+        magnitude
+    };
+
+    Some(truncate_le_bytes(value, u128::BITS))
+}
+
+/// Generates the expression converting a `bytes: [u8; 16]` binding into the inferred integer type, wiping the binding
+///
+/// The number of bits of the absolute value is exposed, to check overflows at compile time.
+/// The literal `expr` is returned by a closure never called, for `cargo check` to report it as
+/// overflowing the inferred type, without the value reaching the binary. It is wrapped in an invisible
+/// group, keeping its spans when the generated code is respanned.
+pub fn decode_inferred_int(magnitude: u128, negative: bool, expr: TokenStream) -> TokenStream {
+    // Without the sign bit
+    let bits = bits_needed(magnitude, negative, true).expect("signed integers can be negative") - 1;
+
+    let expr = Group::new(Delimiter::None, expr);
+
+    quote! { crate::__boo::int::<_, #negative, #bits>(crate::__boo::take(&mut bytes), || #expr) }
+}
+
+/// Gets the little endian bytes of an unsuffixed float from its decimal representation
+///
+/// Both the `f64` and `f32` representations are stored, one of them being picked once its type is inferred.
+///
+/// # Returns
+///
+/// The bytes and whether the value is out of the `f32` range, or `None` if it is out of the `f64` range
+pub fn encode_inferred_float(digits: &str, negative: bool) -> Option<(Vec<u8>, bool)> {
+    let mut bytes = FloatType::find("f64")?.encode(digits, negative)?;
+    let f32_bytes = FloatType::find("f32")?.encode(digits, negative);
+    let f32_overflow = f32_bytes.is_none();
+    bytes.extend(f32_bytes.unwrap_or(vec![0; 4]));
+
+    Some((bytes, f32_overflow))
+}
+
+/// Generates the expression converting a `bytes: [u8; 12]` binding into the inferred float type, wiping the binding
+///
+/// Like integers, the literal `expr` is returned by a closure never called, checked against the inferred type.
+pub fn decode_inferred_float(f32_overflow: bool, expr: TokenStream) -> TokenStream {
+    let expr = Group::new(Delimiter::None, expr);
+
+    quote! { crate::__boo::float::<_, #f32_overflow>(crate::__boo::take(&mut bytes), || #expr) }
+}
+
+/// Gets the number of bits needed to represent an integer, given its absolute value and sign
+///
+/// # Returns
+///
+/// `None` if an unsigned integer is negative
+pub fn bits_needed(magnitude: u128, negative: bool, signed: bool) -> Option<u32> {
+    let bits = match (negative && magnitude != 0, signed) {
+        (true, false) => return None,
+        // Two's complement minimum is `-2^(bits - 1)`
+        (true, true) => u128::BITS - (magnitude - 1).leading_zeros() + 1,
+        (false, true) => u128::BITS - magnitude.leading_zeros() + 1,
+        (false, false) => u128::BITS - magnitude.leading_zeros(),
+    };
+
+    Some(bits)
+}
+
+/// Gets the `bits` lowest bits of `value` as little endian bytes
+///
+/// The macros run on the host, whose endianness may differ from the target: numbers are always encoded
+/// as little endian, and decoded with `from_le_bytes()`.
+fn truncate_le_bytes(value: u128, bits: u32) -> Vec<u8> {
+    value.to_le_bytes()[..bits as usize / 8].to_vec()
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::env::{self, VarError};
+use std::sync::{LazyLock, Mutex};
+
+use crate::chacha20::{BLOCK_LEN, KEY_LEN, NONCE_LEN, chacha20_block};
+use crate::cipher::derive_chacha20_key;
+
+/// Seed making all random values deterministic, for reproducible builds
+pub static SEED: LazyLock<Result<Option<Box<[u8]>>, String>> =
+    LazyLock::new(|| match env::var("BOO_SEED") {
+        Ok(seed) if seed.is_empty() => Err("`BOO_SEED` must not be empty".into()),
+        Ok(seed) => Ok(Some(seed.into_bytes().into())),
+        Err(VarError::NotPresent) => Ok(None),
+        Err(VarError::NotUnicode(_)) => Err("`BOO_SEED` must be valid UTF-8".into()),
+    });
+
+/// Number of expansions per call site location
+///
+/// Tells apart the expansions sharing a location, like the ones generated by a `macro_rules!`.
+/// The compiler always expands a crate in the same order, keeping the count deterministic.
+static OCCURRENCES: LazyLock<Mutex<HashMap<String, u32>>> = LazyLock::new(Default::default);
+
+/// Source of random bytes
+pub enum Random {
+    /// Operating system randomness
+    Os,
+    /// ChaCha20 keystream, keyed by the seed and a domain
+    Seeded { key: [u8; KEY_LEN], counter: u32 },
+}
+
+impl Random {
+    /// Creates a random source for `domain`
+    ///
+    /// When `BOO_SEED` is set, the same seed and domain always produce the same bytes.
+    pub fn new(domain: &str) -> Self {
+        let Ok(Some(seed)) = SEED.as_ref() else {
+            return Self::Os;
+        };
+
+        let mut material = seed.to_vec();
+        material.push(0);
+        material.extend_from_slice(domain.as_bytes());
+
+        Self::Seeded {
+            key: derive_chacha20_key(&material),
+            counter: 0,
+        }
+    }
+
+    /// Creates a random source for the current macro call site
+    ///
+    /// When `BOO_SEED` is set, the bytes are unique per call site location and expansion order.
+    pub fn call_site() -> Self {
+        if !matches!(SEED.as_ref(), Ok(Some(_))) || !proc_macro::is_available() {
+            return Self::new("call_site");
+        }
+
+        let span = proc_macro::Span::call_site();
+        let location = format!(
+            "{}:{}:{}",
+            call_site_file(&span),
+            span.line(),
+            span.column()
+        );
+
+        let mut occurrences = OCCURRENCES.lock().unwrap();
+        let occurrence = occurrences.entry(location.clone()).or_default();
+        *occurrence += 1;
+
+        Self::new(&format!("{location}#{occurrence}"))
+    }
+
+    /// Fills `bytes` with random bytes
+    pub fn fill(&mut self, bytes: &mut [u8]) {
+        match self {
+            Self::Os => rand::fill(bytes),
+            Self::Seeded { key, counter } => {
+                for chunk in bytes.chunks_mut(BLOCK_LEN) {
+                    let block = chacha20_block(key, *counter, &[0; NONCE_LEN]);
+                    chunk.copy_from_slice(&block[..chunk.len()]);
+                    *counter += 1;
+                }
+            }
+        }
+    }
+
+    /// Draws a random array
+    pub fn array<const N: usize>(&mut self) -> [u8; N] {
+        let mut array = [0; N];
+        self.fill(&mut array);
+
+        array
+    }
+}
+
+/// Gets the path of the source file of `span`, relative to the calling crate manifest directory
+///
+/// An absolute path would make the seeded values depend on the directory the sources are checked out in.
+fn call_site_file(span: &proc_macro::Span) -> String {
+    let relative = span.local_file().and_then(|file| {
+        let file = env::current_dir().ok()?.join(file);
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")?;
+        let relative = file.strip_prefix(manifest_dir).ok()?;
+
+        // Joined with `/` on every platform
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_str())
+            .collect::<Option<Vec<_>>>()
+            .map(|components| components.join("/"))
+    });
+
+    relative.unwrap_or_else(|| span.file())
+}
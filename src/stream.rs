@@ -0,0 +1,144 @@
+//! # Warning
+//!
+//! This module is emitted as is by `boo_init!()` into the calling crate.
+//! It must only use `core`, as the calling crate may be `no_std`.
+//!
+//! Streaming decryption of large encrypted literals.
+
+/// Number of bytes decrypted at once by [`BooStream::next_chunk`] and the byte iterator
+pub const STREAM_CHUNK_LEN: usize = 64;
+
+/// Decryptor of an encrypted byte string, yielding its plaintext piece by piece
+///
+/// At most [`STREAM_CHUNK_LEN`] bytes are held decrypted by the stream, and wiped when replaced or dropped.
+/// The subkey is recombined once per stream, and wiped when dropped.
+pub struct BooStream {
+    ciphertext: &'static [u8],
+    subkey: [u8; SUBKEY_LEN],
+    nonce: [u8; 12],
+    /// Offset of the next byte to read
+    position: usize,
+    /// Decrypted bytes from `window_start`
+    window: [u8; STREAM_CHUNK_LEN],
+    window_start: usize,
+    window_len: usize,
+}
+
+impl BooStream {
+    /// Creates a stream over `ciphertext`, encrypted with the subkey of `shares` and `nonce`
+    #[inline(always)]
+    pub fn new(ciphertext: &'static [u8], shares: &'static SubkeyShares, nonce: [u8; 12]) -> Self {
+        Self {
+            ciphertext,
+            subkey: subkey(shares),
+            nonce,
+            position: 0,
+            window: [0; STREAM_CHUNK_LEN],
+            window_start: 0,
+            window_len: 0,
+        }
+    }
+
+    /// Gets the total number of bytes
+    #[inline(always)]
+    pub const fn len(&self) -> usize {
+        self.ciphertext.len()
+    }
+
+    /// Checks if there are no bytes at all
+    #[inline(always)]
+    pub const fn is_empty(&self) -> bool {
+        self.ciphertext.is_empty()
+    }
+
+    /// Gets the number of bytes left to read
+    #[inline(always)]
+    pub const fn remaining(&self) -> usize {
+        self.ciphertext.len() - self.position
+    }
+
+    /// Decrypts the next bytes into `buf`
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written, `0` once the stream is exhausted
+    pub fn read(&mut self, buf: &mut [u8]) -> usize {
+        let len = buf.len().min(self.remaining());
+        let buf = &mut buf[..len];
+        buf.copy_from_slice(&self.ciphertext[self.position..self.position + len]);
+        decrypt_at(buf, &self.subkey, &self.nonce, self.position);
+        self.position += len;
+
+        len
+    }
+
+    /// Decrypts the next chunk of at most [`STREAM_CHUNK_LEN`] bytes, wiping the previous one
+    ///
+    /// # Returns
+    ///
+    /// `None` once the stream is exhausted
+    pub fn next_chunk(&mut self) -> Option<&[u8]> {
+        if self.remaining() == 0 {
+            return None;
+        }
+
+        self.decrypt_window();
+        self.position += self.window_len;
+
+        Some(&self.window[..self.window_len])
+    }
+
+    /// Replaces the decrypted window by the bytes starting at `position`
+    fn decrypt_window(&mut self) {
+        wipe(&mut self.window);
+        let len = STREAM_CHUNK_LEN.min(self.remaining());
+        let window = &mut self.window[..len];
+        window.copy_from_slice(&self.ciphertext[self.position..self.position + len]);
+        decrypt_at(window, &self.subkey, &self.nonce, self.position);
+        self.window_start = self.position;
+        self.window_len = len;
+    }
+}
+
+impl Iterator for BooStream {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.remaining() == 0 {
+            return None;
+        }
+
+        let offset = self.position.wrapping_sub(self.window_start);
+        if offset >= self.window_len {
+            self.decrypt_window();
+        }
+
+        let byte = self.window[self.position - self.window_start];
+        self.position += 1;
+
+        Some(byte)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining(), Some(self.remaining()))
+    }
+}
+
+impl ExactSizeIterator for BooStream {}
+
+impl Drop for BooStream {
+    #[inline(always)]
+    fn drop(&mut self) {
+        wipe(&mut self.window);
+        wipe(&mut self.subkey);
+    }
+}
+
+impl ::core::fmt::Debug for BooStream {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        f.debug_struct("BooStream")
+            .field("len", &self.len())
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
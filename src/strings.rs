@@ -0,0 +1,313 @@
+//! # Warning
+//!
+//! This module is emitted as is by `boo_init!()` into the calling crate.
+//! It must only use `core`, as the calling crate may be `no_std`.
+//!
+//! Decrypted string types stored on the stack, wiped when dropped.
+
+/// Decrypted UTF-8 string stored on the stack
+///
+/// Holds up to `N` bytes, the capacity being shared by all strings of a same array literal.
+pub struct BooStr<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> BooStr<N> {
+    /// Wraps the `len` first bytes of `bytes`
+    ///
+    /// # Safety
+    ///
+    /// `bytes[..len]` must be valid UTF-8.
+    #[inline(always)]
+    pub const unsafe fn from_utf8_unchecked(bytes: [u8; N], len: usize) -> Self {
+        Self { bytes, len }
+    }
+
+    /// Gets the string slice
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        // Safety: checked by `from_utf8_unchecked` callers
+        unsafe { ::core::str::from_utf8_unchecked(&self.bytes[..self.len]) }
+    }
+}
+
+impl<const N: usize> Drop for BooStr<N> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+    }
+}
+
+impl<const N: usize> ::core::ops::Deref for BooStr<N> {
+    type Target = str;
+
+    #[inline(always)]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for BooStr<N> {
+    #[inline(always)]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> ::core::fmt::Display for BooStr<N> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        ::core::fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> ::core::fmt::Debug for BooStr<N> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        ::core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<BooStr<M>> for BooStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &BooStr<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for BooStr<N> {}
+
+impl<const N: usize> PartialEq<str> for BooStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for BooStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<BooStr<N>> for str {
+    #[inline(always)]
+    fn eq(&self, other: &BooStr<N>) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq<BooStr<N>> for &str {
+    #[inline(always)]
+    fn eq(&self, other: &BooStr<N>) -> bool {
+        *self == other.as_str()
+    }
+}
+
+/// Decrypted null-terminated C-string stored on the stack
+///
+/// Holds up to `N` bytes including the null terminator,
+/// the capacity being shared by all C-strings of a same array literal.
+pub struct BooCStr<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> BooCStr<N> {
+    /// Wraps the `len` first bytes of `bytes`
+    ///
+    /// # Safety
+    ///
+    /// `bytes[..len]` must end with the only null byte of the slice.
+    #[inline(always)]
+    pub const unsafe fn from_bytes_with_nul_unchecked(bytes: [u8; N], len: usize) -> Self {
+        Self { bytes, len }
+    }
+
+    /// Gets the C-string slice
+    #[inline(always)]
+    pub fn as_c_str(&self) -> &::core::ffi::CStr {
+        // Safety: checked by `from_bytes_with_nul_unchecked` callers
+        unsafe { ::core::ffi::CStr::from_bytes_with_nul_unchecked(&self.bytes[..self.len]) }
+    }
+
+    /// Gets a pointer to the null-terminated string, valid as long as `self` is not moved or dropped
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *const ::core::ffi::c_char {
+        self.bytes.as_ptr() as *const ::core::ffi::c_char
+    }
+}
+
+impl<const N: usize> Drop for BooCStr<N> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        wipe(&mut self.bytes);
+    }
+}
+
+impl<const N: usize> ::core::ops::Deref for BooCStr<N> {
+    type Target = ::core::ffi::CStr;
+
+    #[inline(always)]
+    fn deref(&self) -> &::core::ffi::CStr {
+        self.as_c_str()
+    }
+}
+
+impl<const N: usize> AsRef<::core::ffi::CStr> for BooCStr<N> {
+    #[inline(always)]
+    fn as_ref(&self) -> &::core::ffi::CStr {
+        self.as_c_str()
+    }
+}
+
+impl<const N: usize> ::core::fmt::Debug for BooCStr<N> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        ::core::fmt::Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<BooCStr<M>> for BooCStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &BooCStr<M>) -> bool {
+        self.as_c_str() == other.as_c_str()
+    }
+}
+
+impl<const N: usize> Eq for BooCStr<N> {}
+
+impl<const N: usize> PartialEq<::core::ffi::CStr> for BooCStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &::core::ffi::CStr) -> bool {
+        self.as_c_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&::core::ffi::CStr> for BooCStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &&::core::ffi::CStr) -> bool {
+        self.as_c_str() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<BooCStr<N>> for ::core::ffi::CStr {
+    #[inline(always)]
+    fn eq(&self, other: &BooCStr<N>) -> bool {
+        self == other.as_c_str()
+    }
+}
+
+impl<const N: usize> PartialEq<BooCStr<N>> for &::core::ffi::CStr {
+    #[inline(always)]
+    fn eq(&self, other: &BooCStr<N>) -> bool {
+        *self == other.as_c_str()
+    }
+}
+
+/// Decrypted null-terminated UTF-16 string stored on the stack
+///
+/// Holds `N` UTF-16 code units, the last one being the null terminator.
+pub struct BooWideStr<const N: usize> {
+    units: [u16; N],
+}
+
+impl<const N: usize> BooWideStr<N> {
+    /// Converts `M` little endian bytes into `N` code units, wiping `bytes`
+    #[inline(always)]
+    pub fn from_le_bytes<const M: usize>(mut bytes: [u8; M]) -> Self {
+        const {
+            if M != N * 2 || N == 0 {
+                panic!("M must be exactly 2 * N, N including the null terminator")
+            }
+        }
+
+        let mut units = [0; N];
+        let mut i = 0;
+        while i < N {
+            units[i] = u16::from_le_bytes([bytes[i * 2], bytes[i * 2 + 1]]);
+            i += 1;
+        }
+        wipe(&mut bytes);
+
+        Self { units }
+    }
+
+    /// Gets the code units, without the null terminator
+    #[inline(always)]
+    pub fn as_slice(&self) -> &[u16] {
+        &self.units[..N - 1]
+    }
+
+    /// Gets the code units, including the null terminator
+    #[inline(always)]
+    pub const fn as_slice_with_nul(&self) -> &[u16; N] {
+        &self.units
+    }
+
+    /// Gets a pointer to the null-terminated string, valid as long as `self` is not moved or dropped
+    #[inline(always)]
+    pub const fn as_ptr(&self) -> *const u16 {
+        self.units.as_ptr()
+    }
+}
+
+impl<const N: usize> Drop for BooWideStr<N> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        wipe(&mut self.units);
+    }
+}
+
+impl<const N: usize> ::core::ops::Deref for BooWideStr<N> {
+    type Target = [u16];
+
+    #[inline(always)]
+    fn deref(&self) -> &[u16] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> AsRef<[u16]> for BooWideStr<N> {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u16] {
+        self.as_slice()
+    }
+}
+
+impl<const N: usize> ::core::fmt::Debug for BooWideStr<N> {
+    fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+        use ::core::fmt::Write;
+
+        f.write_char('"')?;
+        for char in ::core::char::decode_utf16(self.as_slice().iter().copied()) {
+            let char = char.unwrap_or(::core::char::REPLACEMENT_CHARACTER);
+            for escaped in char.escape_debug() {
+                f.write_char(escaped)?;
+            }
+        }
+        f.write_char('"')
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<BooWideStr<M>> for BooWideStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &BooWideStr<M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for BooWideStr<N> {}
+
+impl<const N: usize> PartialEq<[u16]> for BooWideStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &[u16]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&[u16]> for BooWideStr<N> {
+    #[inline(always)]
+    fn eq(&self, other: &&[u16]) -> bool {
+        self.as_slice() == *other
+    }
+}
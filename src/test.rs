@@ -1,6 +1,20 @@
 //! This is a separate file to prevent tests from being included by the macros.
 
-use crate::utils::{split_array, xor};
+use crate::chacha20::{chacha20, chacha20_at, chacha20_block, chacha20_keystream};
+use crate::cipher::Cipher;
+use crate::compress::lz4_compress;
+use crate::encoding::{decode_base64, decode_hex};
+use crate::encrypt_literals::LiteralEncryptor;
+use crate::format::{FormatPiece, encrypt_format, split_format};
+use crate::include_dir::walk_dir;
+use crate::literal_bytes::{BLOB_THRESHOLD, LiteralBytes};
+use crate::lz4::lz4_decompress;
+use crate::random::Random;
+use crate::read_include;
+use crate::utils::{
+    Wiped, ct_eq, split_array, take, wipe, wipe_value, xor, xor_nonce, xor_nonce_at,
+    xor_nonce_keystream,
+};
 
 #[test]
 fn xor_valid_result() {
@@ -34,6 +48,27 @@ fn xor_empty_source() {
     assert!(source.is_empty());
 }
 
+#[test]
+fn xor_nonce_round_trip() {
+    let nonce = [5, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8];
+    let mut source = [0, 1, 2, 3];
+    xor_nonce(&mut source, &[34, 52], &nonce);
+
+    assert_eq!(source, [49, 35, 54, 33]);
+
+    xor_nonce(&mut source, &[34, 52], &nonce);
+
+    assert_eq!(source, [0, 1, 2, 3]);
+}
+
+#[test]
+fn xor_nonce_empty_key() {
+    let mut source = [0, 1, 2, 3];
+    xor_nonce(&mut source, &[], &[1; 12]);
+
+    assert_eq!(source, [0, 1, 2, 3]);
+}
+
 #[test]
 fn split_array_valid_result() {
     let original = [1, 2, 3, 4, 5];
@@ -42,3 +77,572 @@ fn split_array_valid_result() {
     assert_eq!(left, [1, 2]);
     assert_eq!(right, [3, 4, 5]);
 }
+
+#[test]
+fn wipe_zeroes_array() {
+    let mut bytes = [1u8, 2, 3];
+    wipe(&mut bytes);
+    assert_eq!(bytes, [0; 3]);
+
+    let mut units = [0xFFFFu16; 2];
+    wipe(&mut units);
+    assert_eq!(units, [0; 2]);
+}
+
+#[test]
+fn wipe_value_zeroes_memory() {
+    let mut value = (1u8, [2u16, 3], 4.5f64);
+    unsafe { wipe_value(&mut value) };
+
+    assert_eq!(value, (0, [0, 0], 0.0));
+}
+
+#[test]
+fn wiped_wipes_on_unwind() {
+    use std::sync::atomic::{AtomicU8, Ordering};
+
+    static DROPPED: AtomicU8 = AtomicU8::new(u8::MAX);
+
+    // Records its value when dropped, right after the guard wiped it
+    struct Probe(u8);
+
+    impl Drop for Probe {
+        fn drop(&mut self) {
+            DROPPED.store(self.0, Ordering::SeqCst);
+        }
+    }
+
+    let result = std::panic::catch_unwind(|| {
+        let probe = unsafe { Wiped::new(Probe(42)) };
+        assert_eq!(probe.0.0, 42);
+        panic!("closure panicked");
+    });
+
+    assert!(result.is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn take_wipes_source() {
+    let mut bytes = [1, 2, 3];
+    let taken = take(&mut bytes);
+
+    assert_eq!(taken, [1, 2, 3]);
+    assert_eq!(bytes, [0; 3]);
+}
+
+#[test]
+fn xor_nonce_keystream_matches_xor_nonce() {
+    let key = [3, 1, 4, 1, 5];
+    let nonce = [9, 2, 6, 5, 3, 5, 8, 9, 7, 9, 3, 2];
+    let mut keystream = [0; 20];
+    xor_nonce(&mut keystream, &key, &nonce);
+
+    for (i, byte) in keystream.into_iter().enumerate() {
+        assert_eq!(xor_nonce_keystream(&key, &nonce, i), byte);
+    }
+}
+
+#[test]
+fn chacha20_keystream_matches_chacha20() {
+    let key = core::array::from_fn(|i| i as u8);
+    let nonce = [7; 12];
+    let mut keystream = [0; 150];
+    chacha20(&mut keystream, &key, &nonce);
+
+    for (i, byte) in keystream.into_iter().enumerate() {
+        assert_eq!(chacha20_keystream(&key, &nonce, i), byte);
+    }
+}
+
+#[test]
+fn apply_at_offset_matches_whole_keystream() {
+    let key = core::array::from_fn(|i| i as u8);
+    let nonce = [7; 12];
+
+    let mut whole = [0; 150];
+    chacha20(&mut whole, &key, &nonce);
+    let mut part = [0; 70];
+    chacha20_at(&mut part, &key, &nonce, 60);
+    assert_eq!(part, whole[60..130]);
+
+    let mut whole = [0; 40];
+    xor_nonce(&mut whole, &key[..5], &nonce);
+    let mut part = [0; 20];
+    xor_nonce_at(&mut part, &key[..5], &nonce, 13);
+    assert_eq!(part, whole[13..33]);
+}
+
+#[test]
+fn ct_eq_compares_decrypted_bytes() {
+    let keystream = |i: usize| i as u8 * 3 + 1;
+    let ciphertext = [b'a' ^ 1, b'b' ^ 4, b'c' ^ 7];
+
+    assert!(ct_eq(&ciphertext, b"abc", keystream));
+    assert!(!ct_eq(&ciphertext, b"abd", keystream));
+    assert!(!ct_eq(&ciphertext, b"ab", keystream));
+    assert!(!ct_eq(&ciphertext, b"abc\0", keystream));
+    assert!(ct_eq(&[], b"", keystream));
+}
+
+#[test]
+fn chacha20_block_rfc8439() {
+    let key = core::array::from_fn(|i| i as u8);
+    let nonce = [0, 0, 0, 9, 0, 0, 0, 0x4a, 0, 0, 0, 0];
+
+    assert_eq!(
+        chacha20_block(&key, 1, &nonce),
+        [
+            0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20,
+            0x71, 0xc4, 0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03, 0x04, 0x22, 0xaa, 0x9a,
+            0xc3, 0xd4, 0x6c, 0x4e, 0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09, 0x14, 0xc2,
+            0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2, 0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9,
+            0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e,
+        ]
+    );
+}
+
+#[test]
+fn chacha20_zero_keystream() {
+    let mut source = [0; 64];
+    chacha20(&mut source, &[0; 32], &[0; 12]);
+
+    assert_eq!(
+        source,
+        [
+            0x76, 0xb8, 0xe0, 0xad, 0xa0, 0xf1, 0x3d, 0x90, 0x40, 0x5d, 0x6a, 0xe5, 0x53, 0x86,
+            0xbd, 0x28, 0xbd, 0xd2, 0x19, 0xb8, 0xa0, 0x8d, 0xed, 0x1a, 0xa8, 0x36, 0xef, 0xcc,
+            0x8b, 0x77, 0x0d, 0xc7, 0xda, 0x41, 0x59, 0x7c, 0x51, 0x57, 0x48, 0x8d, 0x77, 0x24,
+            0xe0, 0x3f, 0xb8, 0xd8, 0x4a, 0x37, 0x6a, 0x43, 0xb8, 0xf4, 0x15, 0x18, 0xa1, 0x1c,
+            0xc3, 0x87, 0xb6, 0x69, 0xb2, 0xee, 0x65, 0x86,
+        ]
+    );
+}
+
+#[test]
+fn chacha20_round_trip_multiple_blocks() {
+    let original: [u8; 150] = core::array::from_fn(|i| i as u8);
+    let mut source = original;
+    chacha20(&mut source, &[7; 32], &[3; 12]);
+
+    assert_ne!(source, original);
+
+    chacha20(&mut source, &[7; 32], &[3; 12]);
+
+    assert_eq!(source, original);
+}
+
+#[test]
+fn encrypt_identical_literals_differ() {
+    let encrypt = || {
+        LiteralBytes::parse(quote::quote!("same"))
+            .unwrap()
+            .encrypt()
+            .to_string()
+    };
+
+    assert_ne!(encrypt(), encrypt());
+}
+
+#[test]
+fn encrypt_inferred_numbers_check_the_literal() {
+    let encrypt = |tokens| LiteralBytes::parse(tokens).unwrap().encrypt().to_string();
+
+    // The literal is returned by a closure never called, only type checked
+    assert!(encrypt(quote::quote!(-129)).contains("|| - 129)"));
+    assert!(encrypt(quote::quote!(1e40)).contains("|| 1e40)"));
+    assert!(!encrypt(quote::quote!(-129i16)).contains("||"));
+}
+
+#[test]
+fn parse_encodes_little_endian() {
+    let bytes = |tokens| LiteralBytes::parse(tokens).unwrap().as_bytes().to_vec();
+
+    // Whatever the host endianness, decoded with `from_le_bytes()` on the target
+    assert_eq!(bytes(quote::quote!(0x1234u16)), [0x34, 0x12]);
+    assert_eq!(bytes(quote::quote!(-2i32)), [0xFE, 0xFF, 0xFF, 0xFF]);
+    assert_eq!(bytes(quote::quote!(1.0f32)), [0, 0, 0x80, 0x3F]);
+    assert_eq!(bytes(quote::quote!('\u{10FFFF}')), [0xFF, 0xFF, 0x10, 0]);
+    assert_eq!(bytes(quote::quote!(1))[..2], [1, 0]);
+
+    let wide = LiteralBytes::parse_wide(quote::quote!("\u{3A9}")).unwrap();
+    assert_eq!(wide.as_bytes(), [0xA9, 0x03, 0, 0].as_slice());
+}
+
+#[test]
+fn encrypt_large_payload_as_blob() {
+    let encrypt = |len| LiteralBytes::ByteStr(vec![7; len]).encrypt().to_string();
+    let small = encrypt(BLOB_THRESHOLD - 1);
+    let large = encrypt(BLOB_THRESHOLD);
+
+    assert!(!small.contains("let mut bytes = * b\""));
+    assert_eq!(small.matches("u8").count(), BLOB_THRESHOLD - 1);
+    assert!(large.contains("let mut bytes = * b\""));
+    assert!(!large.contains("u8 ,"));
+}
+
+#[test]
+fn encrypt_include_expansion_size() {
+    // Counts the tokens of an expansion, the unit of the compiler work on it
+    fn count(tokens: proc_macro2::TokenStream) -> usize {
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                proc_macro2::TokenTree::Group(group) => 1 + count(group.stream()),
+                _ => 1,
+            })
+            .sum()
+    }
+    let expand = |len| count(LiteralBytes::ByteStr(vec![7; len]).encrypt_include(false));
+
+    // The same number of tokens whatever the size
+    let blob = expand(1);
+    assert!(blob < 100, "{blob} tokens");
+    assert_eq!(expand(BLOB_THRESHOLD), blob);
+    assert_eq!(expand(4 << 20), blob);
+}
+
+#[test]
+fn parse_reports_all_sequence_errors() {
+    let err = match LiteralBytes::parse(quote::quote!((1u8, (300u8, [1, "a"]), 'c', x))) {
+        Ok(_) => panic!("expected errors"),
+        Err(err) => err,
+    };
+    let messages = err
+        .into_iter()
+        .map(|err| err.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0], "integer literal is out of range for `u8`");
+    assert_eq!(messages[1], "array items must share the same type");
+    assert!(messages[2].starts_with("expected one literal parameter"));
+}
+
+#[test]
+fn encrypt_literals_skips_const_contexts() {
+    let mut item = syn::parse_quote! {
+        #[doc = "kept"]
+        fn f(x: [u8; 2]) -> u8 {
+            const KEPT: u8 = 1;
+            let _ = [0u8; 3];
+            println!("{} kept {}", "encrypted", 7);
+            match x {
+                [4, _] => 5,
+                _ => 6,
+            }
+        }
+    };
+    syn::visit_mut::VisitMut::visit_item_fn_mut(&mut LiteralEncryptor::default(), &mut item);
+    let tokens = quote::ToTokens::to_token_stream(&item).to_string();
+
+    for kept in [
+        "\"kept\"",
+        "[u8 ; 2]",
+        "const KEPT : u8 = 1",
+        "; 3]",
+        "\"{} kept {}\"",
+        "[4 , _]",
+    ] {
+        assert!(tokens.contains(kept), "{kept} in {tokens}");
+    }
+    // Encrypted literals are parenthesized, the random ciphertext may contain any other text
+    for encrypted in ["\"{} kept {}\" , (", "_] => (", "_ => (", "let _ = [("] {
+        assert!(tokens.contains(encrypted), "{encrypted} not in {tokens}");
+    }
+    assert!(!tokens.contains("\"encrypted\""), "{tokens}");
+
+    let mut item = syn::parse_quote! {
+        trait T<const N: usize = 8> {
+            const KEPT: u8 = 9;
+            fn f() -> u8 {
+                10
+            }
+        }
+    };
+    syn::visit_mut::VisitMut::visit_item_trait_mut(&mut LiteralEncryptor::default(), &mut item);
+    let tokens = quote::ToTokens::to_token_stream(&item).to_string();
+
+    assert!(tokens.contains("const N : usize = 8"), "{tokens}");
+    assert!(tokens.contains("const KEPT : u8 = 9"), "{tokens}");
+    assert!(tokens.contains("u8 { ("), "{tokens}");
+}
+
+#[test]
+fn encrypt_literals_in_macros() {
+    let mut item = syn::parse_quote! {
+        fn f() {
+            custom!("kept", "encrypted", 1);
+            custom!(2, "{} kept");
+            std::println!("kept {}", 3);
+            tracing::info!(count = 4, "kept");
+            concat!("kept", 5);
+            vec![6u8; 7];
+            info!("kept");
+            log::info!(target: "kept", "kept {}", 8);
+            custom!(=> "kept");
+            core::arch::asm!("nop", "nop");
+        }
+    };
+    syn::visit_mut::VisitMut::visit_item_fn_mut(&mut LiteralEncryptor::default(), &mut item);
+    let tokens = quote::ToTokens::to_token_stream(&item).to_string();
+
+    for kept in [
+        "\"{} kept\"",
+        "\"kept {}\"",
+        "\"kept\"",
+        "concat ! (\"kept\" , 5)",
+        "; 7]",
+        "info ! (\"kept\")",
+        "target : \"kept\" , \"kept {}\"",
+        "(=> \"kept\")",
+        "asm ! (\"nop\" , \"nop\")",
+    ] {
+        assert!(tokens.contains(kept), "{kept} in {tokens}");
+    }
+    // Encrypted arguments are parenthesized, the random ciphertext may contain any other text
+    for encrypted in [
+        "custom ! (\"kept\" , (",
+        "custom ! ((",
+        "std :: println ! (\"kept {}\" , (",
+        "count = (",
+        "vec ! [(",
+        "target : \"kept\" , \"kept {}\" , (",
+    ] {
+        assert!(tokens.contains(encrypted), "{encrypted} not in {tokens}");
+    }
+    assert!(!tokens.contains("\"encrypted\""), "{tokens}");
+    assert_eq!(tokens.matches("struct BooWarning").count(), 1);
+    assert!(tokens.starts_with("fn f () { const _ : () ="));
+}
+
+#[test]
+fn encrypt_format_checks_positional_arguments() {
+    let valid = [
+        quote::quote! { "{} {}", 1, 2 },
+        quote::quote! { "{1}-{0}-{1}", 'a', 'b' },
+        quote::quote! { "[{:>1$}] {name}", 1, 6 },
+        quote::quote! { "{:.*}", 2, 1.5 },
+        quote::quote! { "{:0<5} {:1>5}", 1, 2 },
+        quote::quote! { "{:>width$.prec$}", 1 },
+        quote::quote! { "no placeholders" },
+    ];
+    for tokens in valid {
+        assert!(encrypt_format(tokens.clone(), false).is_ok(), "{tokens}");
+    }
+
+    let invalid = [
+        (
+            quote::quote! { "a {} b {}", 1 },
+            "positional argument 1 (there is 1 argument)",
+        ),
+        (
+            quote::quote! { "{:1$}", 1 },
+            "positional argument 1 (there is 1 argument)",
+        ),
+        (
+            quote::quote! { "{:.*}", 1.5 },
+            "positional argument 1 (there is 1 argument)",
+        ),
+        (
+            quote::quote! { "{0}" },
+            "positional argument 0 (there are no arguments)",
+        ),
+        (
+            quote::quote! { "{} {} {3}", 1, 2, 3 },
+            "positional argument 3 (there are 3 arguments)",
+        ),
+    ];
+    for (tokens, message) in invalid {
+        let err = encrypt_format(tokens.clone(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.ends_with(message), "{tokens}: {err}");
+    }
+}
+
+#[test]
+fn split_format_valid_result() {
+    use FormatPiece::{Placeholder, Text};
+
+    assert_eq!(
+        split_format("a {} b {0:?}{name:>1$} {{c}}"),
+        Ok(vec![
+            Text("a ".into()),
+            Placeholder("{}".into()),
+            Text(" b ".into()),
+            Placeholder("{0:?}".into()),
+            Placeholder("{name:>1$}".into()),
+            Text(" {c}".into()),
+        ])
+    );
+    assert_eq!(split_format(""), Ok(vec![]));
+}
+
+#[test]
+fn split_format_unbalanced() {
+    assert!(split_format("a {").is_err());
+    assert!(split_format("a }").is_err());
+}
+
+#[test]
+fn subkey_depends_on_salt() {
+    let cipher = Cipher::SELECTED;
+
+    assert_eq!(cipher.subkey(&[1; 12]), cipher.subkey(&[1; 12]));
+    assert_ne!(cipher.subkey(&[1; 12]), cipher.subkey(&[2; 12]));
+}
+
+#[test]
+fn subkey_does_not_reveal_key() {
+    let salt = [9, 8, 7, 6, 5, 4, 3, 2, 1, 0, 1, 2];
+    let keys = [Cipher::key().to_vec(), crate::key().to_vec()];
+
+    for cipher in [Cipher::Xor, Cipher::ChaCha20] {
+        let subkey = cipher.subkey(&salt);
+        // Removing the salt reversed the former XOR derivation
+        let mut unsalted = subkey.clone();
+        xor_nonce(&mut unsalted, &[0], &salt);
+
+        for key in &keys {
+            for candidate in [&subkey, &unsalted] {
+                assert!(
+                    !candidate
+                        .windows(4)
+                        .any(|window| key.windows(4).any(|key| key == window))
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn decode_hex_valid_result() {
+    assert_eq!(decode_hex("00ff10Ab"), Ok(vec![0x00, 0xff, 0x10, 0xab]));
+    assert_eq!(decode_hex(""), Ok(vec![]));
+}
+
+#[test]
+fn decode_hex_invalid() {
+    assert!(decode_hex("abc").is_err());
+    assert!(decode_hex("0g").is_err());
+}
+
+#[test]
+fn decode_base64_valid_result() {
+    assert_eq!(decode_base64("Zm9vYmFy"), Ok(b"foobar".to_vec()));
+    assert_eq!(decode_base64("Zm9vYg=="), Ok(b"foob".to_vec()));
+    assert_eq!(decode_base64("Zm9vYg"), Ok(b"foob".to_vec()));
+    assert_eq!(decode_base64("+/+/"), Ok(vec![0xfb, 0xff, 0xbf]));
+}
+
+#[test]
+fn decode_base64_invalid() {
+    assert!(decode_base64("Zm9vY").is_err());
+    assert!(decode_base64("Zm9v-mFy").is_err());
+}
+
+#[test]
+fn seeded_random_is_deterministic() {
+    let seeded = || Random::Seeded {
+        key: [1; 32],
+        counter: 0,
+    };
+    let (mut first, mut second) = (seeded(), seeded());
+
+    assert_eq!(first.array::<100>(), second.array::<100>());
+    assert_ne!(first.array::<12>(), seeded().array::<12>());
+}
+
+#[test]
+fn walk_dir_lists_nested_files() {
+    let root = std::env::temp_dir().join(format!("boo-walk-dir-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("b/c")).unwrap();
+    for name in ["b/c/d.txt", "a.txt", "b/e.txt"] {
+        std::fs::write(root.join(name), name).unwrap();
+    }
+
+    let files = walk_dir(&root);
+    std::fs::remove_dir_all(&root).unwrap();
+    let names = files
+        .unwrap()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, ["a.txt", "b/c/d.txt", "b/e.txt"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn read_include_keeps_flag_errors() {
+    let error = |tokens| {
+        read_include(tokens, &["compress"])
+            .err()
+            .map(|err| err.to_string())
+    };
+
+    assert_eq!(error(quote::quote!("README.md", compress)), None);
+    assert_eq!(
+        error(quote::quote!("README.md", zip)).as_deref(),
+        Some("unsupported flag")
+    );
+    // Invalid paths are reported with the usage
+    assert!(
+        error(quote::quote!(3))
+            .unwrap()
+            .ends_with("optionally followed by `, compress`")
+    );
+}
+
+#[test]
+fn walk_dir_skips_symlinked_dirs() {
+    let root = std::env::temp_dir().join(format!("boo-walk-dir-links-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("a")).unwrap();
+    std::fs::write(root.join("a/b.txt"), "b").unwrap();
+    std::os::unix::fs::symlink(&root, root.join("a/loop")).unwrap();
+    std::os::unix::fs::symlink(root.join("a/b.txt"), root.join("c.txt")).unwrap();
+
+    let files = walk_dir(&root);
+    std::fs::remove_dir_all(&root).unwrap();
+    let names = files
+        .unwrap()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, ["a/b.txt", "c.txt"]);
+}
+
+#[test]
+fn lz4_round_trip() {
+    let mut random = Random::Seeded {
+        key: [3; 32],
+        counter: 0,
+    };
+    let noise = random.array::<1000>().to_vec();
+    let repeated = b"boo! ".repeat(200);
+    let mixed = [&noise[..300], &repeated[..], &noise[..300], &[0; 5000][..]].concat();
+
+    for source in [
+        &[][..],
+        b"a",
+        b"hello world",
+        &[7; 300],
+        &noise,
+        &repeated,
+        &mixed,
+    ] {
+        let compressed = lz4_compress(source);
+        let mut decompressed = vec![0; source.len()];
+
+        assert_eq!(
+            lz4_decompress(compressed.iter().copied(), &mut decompressed),
+            source.len()
+        );
+        assert_eq!(decompressed, source);
+    }
+
+    assert!(lz4_compress(&repeated).len() < 50);
+    assert!(lz4_compress(&mixed).len() < 1000);
+}
@@ -14,6 +14,144 @@ pub const fn xor(source: &mut [u8], key: &[u8]) {
     }
 }
 
+/// Applies XOR between each byte of `source` and a keystream mixing the wrapped `key` with `nonce`.
+///
+/// The first four bytes of `nonce` select the starting offset in `key`,
+/// then each keystream byte is also XORed with the wrapped `nonce`.
+pub const fn xor_nonce(source: &mut [u8], key: &[u8], nonce: &[u8; 12]) {
+    xor_nonce_at(source, key, nonce, 0);
+}
+
+/// Applies XOR between each byte of `source` and the [`xor_nonce`] keystream, starting at byte `offset`.
+pub const fn xor_nonce_at(source: &mut [u8], key: &[u8], nonce: &[u8; 12], offset: usize) {
+    if !key.is_empty() {
+        let mut i = 0;
+        while i < source.len() {
+            source[i] ^= xor_nonce_keystream(key, nonce, offset + i);
+            i += 1;
+        }
+    }
+}
+
+/// Overwrites `array` with default values, without the compiler optimizing the writes away
+#[allow(unused)]
+#[inline(always)]
+pub fn wipe<T: Copy + Default, const N: usize>(array: &mut [T; N]) {
+    let ptr = array.as_mut_ptr();
+    let mut i = 0;
+    while i < N {
+        // Safety: `i` is in bounds
+        unsafe { ::core::ptr::write_volatile(ptr.add(i), T::default()) };
+        i += 1;
+    }
+
+    // Prevents the writes from being reordered after a later read of the memory
+    ::core::sync::atomic::compiler_fence(::core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Overwrites the memory of `value` with zeros, without the compiler optimizing the writes away
+///
+/// # Safety
+///
+/// All zero bytes must be a valid `T`.
+#[allow(unused)]
+#[inline(always)]
+pub unsafe fn wipe_value<T>(value: &mut T) {
+    let ptr = value as *mut T as *mut u8;
+    let mut i = 0;
+    while i < ::core::mem::size_of::<T>() {
+        // Safety: `i` is in bounds
+        unsafe { ::core::ptr::write_volatile(ptr.add(i), 0) };
+        i += 1;
+    }
+
+    ::core::sync::atomic::compiler_fence(::core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Guard wiping the wrapped value with [`wipe_value`] when dropped, including on unwind
+#[allow(unused)]
+pub struct Wiped<T>(pub T);
+
+impl<T> Wiped<T> {
+    /// Wraps `value`, to be wiped when the guard is dropped
+    ///
+    /// # Safety
+    ///
+    /// All zero bytes must be a valid `T`.
+    #[allow(unused)]
+    #[inline(always)]
+    pub const unsafe fn new(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Drop for Wiped<T> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        // Safety: checked by the caller of `new`
+        unsafe { wipe_value(&mut self.0) };
+    }
+}
+
+/// Moves the content of `bytes` out, wiping the original array
+#[allow(unused)]
+#[inline(always)]
+pub fn take<const N: usize>(bytes: &mut [u8; N]) -> [u8; N] {
+    let taken = *bytes;
+    wipe(bytes);
+
+    taken
+}
+
+/// Recombines a literal subkey from its `shares`, XORed together
+///
+/// The shares are read with volatile reads, preventing the compiler from folding them into the subkey.
+#[allow(unused)]
+#[inline(always)]
+pub fn subkey<const N: usize, const SHARES: usize>(shares: &'static [[u8; N]; SHARES]) -> [u8; N] {
+    let mut subkey = [0; N];
+    let mut i = 0;
+    while i < SHARES {
+        // Safety: the share is a valid reference
+        let share = unsafe { ::core::ptr::read_volatile(&shares[i]) };
+        xor(&mut subkey, &share);
+        i += 1;
+    }
+
+    subkey
+}
+
+/// Lends `value` to `f`, letting the compiler infer the type of an untyped closure parameter
+#[allow(unused)]
+#[inline(always)]
+pub fn lend<T, R>(value: &T, f: impl FnOnce(&T) -> R) -> R {
+    f(value)
+}
+
+/// Gets the byte at `index` of the keystream applied by [`xor_nonce`], `key` must not be empty.
+pub const fn xor_nonce_keystream(key: &[u8], nonce: &[u8; 12], index: usize) -> u8 {
+    let offset = u32::from_le_bytes([nonce[0], nonce[1], nonce[2], nonce[3]]) as usize;
+    key[(offset % key.len() + index) % key.len()] ^ nonce[index % 12]
+}
+
+/// Compares `input` with the decryption of `ciphertext`, one keystream byte at a time.
+///
+/// The plaintext is never stored, and the time taken only depends on the lengths.
+#[allow(unused)]
+#[inline(never)]
+pub fn ct_eq(ciphertext: &[u8], input: &[u8], keystream: impl Fn(usize) -> u8) -> bool {
+    let mut diff = (ciphertext.len() != input.len()) as u8;
+    let mut i = 0;
+    while i < ciphertext.len() {
+        let byte = if i < input.len() { input[i] } else { 0 };
+        diff |= ciphertext[i] ^ keystream(i) ^ byte;
+        i += 1;
+    }
+
+    // Prevents the compiler from turning the loop into an early return
+    ::core::hint::black_box(diff) == 0
+}
+
 /// Splits a `[u8; LEN]` array into two owned arrays `[u8; LEFT]` and `[u8; RIGHT]` without allocating.
 #[allow(unused)]
 #[inline(always)]
@@ -39,3 +177,104 @@ pub const fn split_array<const LEN: usize, const LEFT: usize, const RIGHT: usize
 
     unsafe { (left_ptr.read(), right_ptr.read()) }
 }
+
+/// Integer types an unsuffixed integer literal can be decrypted into
+pub trait FromBooInt {
+    /// Size in bits
+    const BITS: u32;
+    /// Whether the type accepts negative values
+    const SIGNED: bool;
+
+    /// Truncates a two's complement 128 bits integer
+    fn from_u128(value: u128) -> Self;
+}
+
+macro_rules! impl_from_boo_int {
+    ($($ty:ty),*) => {$(
+        impl FromBooInt for $ty {
+            const BITS: u32 = <$ty>::BITS;
+            const SIGNED: bool = <$ty>::MIN != 0;
+
+            #[inline(always)]
+            fn from_u128(value: u128) -> Self {
+                value as Self
+            }
+        }
+    )*};
+}
+
+impl_from_boo_int!(
+    u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+);
+
+/// Converts the bytes of an unsuffixed integer literal into the inferred integer type `T`.
+///
+/// The literal absolute value needs `BITS` bits, plus the sign bit of signed types:
+/// an overflow of `T` fails at compile time.
+/// `_inference` returns the unsuffixed literal and is never called: it makes `T` fallback to `i32` like
+/// any integer literal, and lets `cargo check` report an overflow of `T` on the literal, before this
+/// function is even instantiated.
+#[allow(unused)]
+#[inline(always)]
+pub fn int<T: FromBooInt, const NEGATIVE: bool, const BITS: u32>(
+    bytes: [u8; 16],
+    _inference: impl FnOnce() -> T,
+) -> T {
+    const {
+        if NEGATIVE && !T::SIGNED || BITS + T::SIGNED as u32 > T::BITS {
+            panic!("integer literal is out of range for the inferred type")
+        }
+    }
+
+    T::from_u128(u128::from_le_bytes(bytes))
+}
+
+/// Float types an unsuffixed float literal can be decrypted into
+pub trait FromBooFloat {
+    /// Whether the type is `f32`
+    const F32: bool;
+
+    /// Picks the representation of the type
+    fn from_boo_bytes(f64_bytes: [u8; 8], f32_bytes: [u8; 4]) -> Self;
+}
+
+impl FromBooFloat for f32 {
+    const F32: bool = true;
+
+    #[inline(always)]
+    fn from_boo_bytes(_: [u8; 8], f32_bytes: [u8; 4]) -> Self {
+        f32::from_le_bytes(f32_bytes)
+    }
+}
+
+impl FromBooFloat for f64 {
+    const F32: bool = false;
+
+    #[inline(always)]
+    fn from_boo_bytes(f64_bytes: [u8; 8], _: [u8; 4]) -> Self {
+        f64::from_le_bytes(f64_bytes)
+    }
+}
+
+/// Converts the bytes of an unsuffixed float literal into the inferred float type `T`.
+///
+/// Both `f64` and `f32` representations are encrypted, avoiding a double rounding.
+/// `F32_OVERFLOW` makes an `f32` inference fail at compile time.
+/// `_inference` returns the unsuffixed literal and is never called, making `T` fallback to `f64` like any
+/// float literal.
+#[allow(unused)]
+#[inline(always)]
+pub fn float<T: FromBooFloat, const F32_OVERFLOW: bool>(
+    bytes: [u8; 12],
+    _inference: impl FnOnce() -> T,
+) -> T {
+    const {
+        if F32_OVERFLOW && T::F32 {
+            panic!("float literal is out of range for `f32`")
+        }
+    }
+
+    let (f64_bytes, f32_bytes) = split_array::<12, 8, 4>(bytes);
+
+    T::from_boo_bytes(f64_bytes, f32_bytes)
+}
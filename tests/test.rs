@@ -1,4 +1,5 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+#![allow(clippy::octal_escapes, clippy::byte_char_slices)]
 
 extern crate alloc;
 #[macro_use]
@@ -8,6 +9,12 @@ use alloc::borrow::ToOwned;
 
 boo_init!();
 
+boo_static! {
+    static API_URL: &str = "https://example.com";
+    pub(crate) static PORT: u16 = 443;
+    static HEADERS: [(&str, &core::ffi::CStr); 2] = [("a", c"b"), ("cd", c"e")];
+}
+
 #[test]
 fn test_literal_bool() {
     assert!(boo!(true));
@@ -16,7 +23,8 @@ fn test_literal_bool() {
 
 #[test]
 fn test_literal_isize() {
-    assert_eq!(boo!(-9223372036854775808), -9223372036854775808);
+    // Unsuffixed integers fall back to `i32`, the expected value sets the type
+    assert_eq!(boo!(-9223372036854775808), -9223372036854775808isize);
 
     assert_eq!(boo!(-1), -1);
 
@@ -26,7 +34,69 @@ fn test_literal_isize() {
 
     assert_eq!(boo!(1), 1);
 
-    assert_eq!(boo!(9223372036854775807), 9223372036854775807);
+    assert_eq!(boo!(9223372036854775807), 9223372036854775807isize);
+}
+
+#[test]
+fn test_literal_int_suffix() {
+    assert_eq!(boo!(255u8), u8::MAX);
+    assert_eq!(boo!(65535u16), u16::MAX);
+    assert_eq!(boo!(4294967295u32), u32::MAX);
+    assert_eq!(boo!(18446744073709551615u64), u64::MAX);
+    assert_eq!(boo!(340282366920938463463374607431768211455u128), u128::MAX);
+    assert_eq!(boo!(0usize), 0usize);
+
+    assert_eq!(boo!(-128i8), i8::MIN);
+    assert_eq!(boo!(-32768i16), i16::MIN);
+    assert_eq!(boo!(-2147483648i32), i32::MIN);
+    assert_eq!(boo!(-9223372036854775808i64), i64::MIN);
+    assert_eq!(
+        boo!(-170141183460469231731687303715884105728i128),
+        i128::MIN
+    );
+    assert_eq!(boo!(127i8), i8::MAX);
+    assert_eq!(boo!(-1isize), -1isize);
+
+    assert_eq!(boo!(0xffu8), 0xff);
+    assert_eq!(boo!(1_000u16), 1_000);
+}
+
+#[test]
+fn test_literal_int_inferred() {
+    let port: u16 = boo!(443);
+    assert_eq!(port, 443);
+
+    let min: i8 = boo!(-128);
+    assert_eq!(min, i8::MIN);
+
+    let max: u128 = boo!(340282366920938463463374607431768211455);
+    assert_eq!(max, u128::MAX);
+
+    let default = boo!(-1);
+    assert_eq!(default, -1i32);
+
+    assert_eq!(boo!([1, 2, 3]), [1u8, 2, 3]);
+    assert_eq!(boo!((1, -2)), (1u64, -2i16));
+}
+
+#[test]
+fn test_literal_float_inferred() {
+    let float: f32 = boo!(0.1);
+    assert_eq!(float, 0.1f32);
+
+    let float: f32 = boo!(16777217.0);
+    assert_eq!(float, 16777217.0f32);
+
+    let default = boo!(0.1);
+    assert_eq!(default, 0.1f64);
+}
+
+#[test]
+fn test_literal_float_suffix() {
+    assert_eq!(boo!(3.4028235e38f32), f32::MAX);
+    assert_eq!(boo!(-1.5f32), -1.5f32);
+    assert_eq!(boo!(0.1f32), 0.1f32);
+    assert_eq!(boo!(0.1f64), 0.1f64);
 }
 
 #[test]
@@ -39,8 +109,9 @@ fn test_literal_f64() {
     assert_eq!(boo!(-1.), -1.);
 
     assert_eq!(boo!(0.), 0.);
-    assert_eq!(boo!(0.0).is_sign_positive(), 0f64.is_sign_positive());
-    assert_eq!(boo!(-0.0).is_sign_positive(), (-0f64).is_sign_positive());
+    // Unsuffixed floats need a type before calling a method on them
+    assert_eq!(boo!(0.0f64).is_sign_positive(), 0f64.is_sign_positive());
+    assert_eq!(boo!(-0.0f64).is_sign_positive(), (-0f64).is_sign_positive());
 
     assert_eq!(boo!(1.), 1.);
 
@@ -97,6 +168,17 @@ fn test_literal_str() {
     );
 }
 
+#[test]
+fn test_literal_str_api() {
+    let str = boo!("hello");
+
+    assert_eq!(str.len(), 5);
+    assert_eq!(str.as_ref(), "hello");
+    assert_eq!(alloc::format!("{str} {str:?}"), "hello \"hello\"");
+    assert!("hello" == str);
+    assert_eq!(str, boo!("hello"));
+}
+
 #[test]
 fn test_literal_bstr() {
     assert_eq!(boo!(b"\0\n\\ 09azAZ"), b"\0\n\\ 09azAZ".to_owned());
@@ -134,6 +216,40 @@ fn test_literal_cstr() {
     );
 }
 
+#[test]
+fn test_literal_cstr_api() {
+    let cstr = boo!(c"hello");
+
+    assert_eq!(cstr.to_bytes(), b"hello");
+    assert_eq!(cstr.as_ref(), c"hello");
+    assert!(c"hello" == cstr);
+    assert_eq!(cstr, boo!(c"hello"));
+
+    let from_ptr = unsafe { core::ffi::CStr::from_ptr(cstr.as_ptr()) };
+    assert_eq!(from_ptr, c"hello");
+}
+
+#[test]
+fn test_literal_wide_str() {
+    let expected: alloc::vec::Vec<u16> = "abc \u{10FFFF}".encode_utf16().collect();
+    assert_eq!(boo_wide!("abc \u{10FFFF}"), &expected[..]);
+    assert_eq!(boo_wide!(""), &[][..]);
+    assert_eq!(boo_wide!(r"\n").as_slice(), &[b'\\' as u16, b'n' as u16]);
+}
+
+#[test]
+fn test_literal_wide_str_api() {
+    let wide = boo_wide!("hi");
+
+    assert_eq!(wide.len(), 2);
+    assert_eq!(wide.as_slice_with_nul(), &[b'h' as u16, b'i' as u16, 0]);
+    assert_eq!(alloc::format!("{wide:?}"), "\"hi\"");
+    assert_eq!(wide, boo_wide!("hi"));
+
+    let from_ptr = unsafe { core::slice::from_raw_parts(wide.as_ptr(), 3) };
+    assert_eq!(from_ptr, wide.as_slice_with_nul());
+}
+
 #[test]
 fn test_literal_array_bool() {
     assert_eq!(boo!([true, false]), [true, false]);
@@ -189,7 +305,7 @@ fn test_literal_bool_sequence() {
 fn test_literal_isize_sequence() {
     assert_eq!(
         boo!([-9223372036854775808, -1, 0, 1, 9223372036854775807]),
-        [-9223372036854775808, -1, 0, 1, 9223372036854775807]
+        [-9223372036854775808isize, -1, 0, 1, 9223372036854775807]
     );
     assert_eq!(boo!([[1], [2], [3]]), [[1], [2], [3]]);
     assert_eq!(boo!((1, 2, 3)), (1, 2, 3));
@@ -221,6 +337,13 @@ fn test_literal_f64_sequence() {
     assert_eq!(boo!((0.0, (1.0, -1.0))), (0.0, (1.0, -1.0)));
 }
 
+#[test]
+fn test_literal_suffix_sequence() {
+    assert_eq!(boo!([1u8, 2u8, 3u8]), [1u8, 2, 3]);
+    assert_eq!(boo!([1u8, 2, 3]), [1u8, 2, 3]);
+    assert_eq!(boo!((1u8, -1i64, 1.5f32)), (1u8, -1i64, 1.5f32));
+}
+
 #[test]
 fn test_literal_char_sequence() {
     assert_eq!(
@@ -250,11 +373,13 @@ fn test_literal_str_sequence() {
         ["\0", "\n", "\\", "abc", "ABC", "\u{10FFFF}"]
     );
     assert_eq!(boo!([["a"], ["b"]]), [["a"], ["b"]]);
-    assert_eq!(boo!(("a", "b")), ("a".to_owned(), "b".to_owned()));
-    assert_eq!(
-        boo!(("a", ("b", "c"))),
-        ("a".to_owned(), ("b".to_owned(), "c".to_owned()))
-    );
+    let (a, b) = boo!(("a", "b"));
+    assert_eq!((a.as_str(), b.as_str()), ("a", "b"));
+    let (a, (b, c)) = boo!(("a", ("b", "c")));
+    assert_eq!((&*a, (&*b, &*c)), ("a", ("b", "c")));
+    assert_eq!(boo!([["a", ""], ["bcd", "ef"]]), [["a", ""], ["bcd", "ef"]]);
+    let [(a, one), (bcd, two)] = boo!([("a", 1), ("bcd", 2)]);
+    assert_eq!((&*a, one, &*bcd, two), ("a", 1, "bcd", 2));
 }
 
 #[test]
@@ -281,25 +406,400 @@ fn test_literal_cstr_sequence() {
         [c"abc", c"\n\\ ", c"\u{10FFFF}"]
     );
     assert_eq!(boo!([[c"a"], [c"b"]]), [[c"a"], [c"b"]]);
-    assert_eq!(boo!((c"a", c"b")), (c"a".to_owned(), c"b".to_owned()));
+    let (a, b) = boo!((c"a", c"b"));
+    assert_eq!((a.as_c_str(), b.as_c_str()), (c"a", c"b"));
+    let (a, (b, c)) = boo!((c"a", (c"b", c"c")));
+    assert_eq!((&*a, (&*b, &*c)), (c"a", (c"b", c"c")));
+    assert_eq!(boo!([[c"a"], [c"bcd"]]), [[c"a"], [c"bcd"]]);
+}
+
+#[test]
+fn test_with() {
+    assert_eq!(boo_with!("secret", |s: &str| s.len()), 6);
+    assert!(boo_with!(c"secret", |s: &core::ffi::CStr| s == c"secret"));
+    assert!(boo_with!(b"\x01\x02", |b: &[u8; 2]| b == &[1, 2]));
+    assert_eq!(boo_with!(-3, |n: &i8| *n), -3);
+    assert_eq!(boo_with!(1.5, |f: &f32| f * 2.0), 3.0);
+    assert_eq!(boo_with!('x', |c: &char| *c), 'x');
+    assert!(boo_with!(true, |b: &bool| *b));
+}
+
+#[test]
+fn test_with_sequence() {
     assert_eq!(
-        boo!((c"a", (c"b", c"c"))),
-        (c"a".to_owned(), (c"b".to_owned(), c"c".to_owned()))
+        boo_with!(["a", "bc"], |items| items
+            .iter()
+            .map(|s| s.len())
+            .sum::<usize>()),
+        3
+    );
+    assert!(boo_with!(("a", (1u8, c"b")), |(a, (n, b))| a == "a"
+        && *n == 1
+        && b.as_c_str() == c"b"));
+    assert_eq!(
+        boo_with!([[1, 2], [3, 4]], |nested: &[[u32; 2]; 2]| nested[1][0]),
+        3
     );
 }
 
+#[test]
+fn test_eq() {
+    assert!(boo_eq!("password", "password"));
+    assert!(boo_eq!(alloc::string::String::from("password"), "password"));
+    assert!(!boo_eq!("passwore", "password"));
+    assert!(!boo_eq!("pass", "password"));
+    assert!(!boo_eq!("password!", "password"));
+    assert!(boo_eq!("", ""));
+    assert!(boo_eq!([1u8, 2, 3], b"\x01\x02\x03"));
+    assert!(boo_eq!(b"\xFF".as_slice(), b"\xFF"));
+}
+
 #[test]
 fn test_include_bytes() {
+    let bytes = boo_include_bytes!("../assets/lorem_ipsum.txt");
+    let mut buffer = [0; 26];
+    assert_eq!(bytes.len(), 26);
     assert_eq!(
-        &boo_include_bytes!("../assets/lorem_ipsum.txt"),
+        bytes.decrypt_into(&mut buffer),
         b"Lorem ipsum dolor sit amet"
     );
 }
 
+#[test]
+fn test_include_relative_to_source_file() {
+    let blob = boo_include_bytes!("test.rs");
+    let mut buffer = alloc::vec![0; blob.len()];
+    assert_eq!(blob.decrypt_into(&mut buffer), include_bytes!("test.rs"));
+}
+
+#[test]
+fn test_include_blob() {
+    let bytes = boo_include_bytes!("../assets/boo.png");
+    let mut buffer = alloc::vec![0; bytes.len() + 1];
+    assert_eq!(bytes.len(), include_bytes!("../assets/boo.png").len());
+    assert_eq!(
+        bytes.decrypt_into(&mut buffer),
+        include_bytes!("../assets/boo.png")
+    );
+
+    let str = boo_include_str!("test.rs");
+    let mut buffer = alloc::vec![0; str.len()];
+    assert_eq!(str.decrypt_into(&mut buffer), include_str!("test.rs"));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_include_blob_alloc() {
+    assert_eq!(
+        boo_include_bytes!("../assets/boo.png").decrypt_to_vec(),
+        include_bytes!("../assets/boo.png")
+    );
+    assert_eq!(
+        boo_include_str!("test.rs").decrypt_to_string(),
+        include_str!("test.rs")
+    );
+}
+
+#[test]
+fn test_include_manifest() {
+    let mut buffer = [0; 26];
+    assert_eq!(
+        boo_include_bytes!(manifest = "assets/lorem_ipsum.txt").decrypt_into(&mut buffer),
+        b"Lorem ipsum dolor sit amet"
+    );
+    assert_eq!(
+        boo_include_str!(manifest = "assets/lorem_ipsum.txt").decrypt_into(&mut buffer),
+        "Lorem ipsum dolor sit amet"
+    );
+    assert_eq!(boo_stream!(manifest = "assets/lorem_ipsum.txt").count(), 26);
+}
+
 #[test]
 fn test_include_str() {
+    let str = boo_include_str!("../assets/lorem_ipsum.txt");
+    let mut buffer = [0; 26];
+    assert_eq!(str.len(), 26);
+    assert_eq!(str.decrypt_into(&mut buffer), "Lorem ipsum dolor sit amet");
+}
+
+#[test]
+fn test_include_compressed() {
+    let png = boo_include_bytes!("../assets/boo.png", compress);
+    let mut buffer = alloc::vec![0; png.len()];
+    assert_eq!(png.len(), include_bytes!("../assets/boo.png").len());
     assert_eq!(
-        boo_include_str!("../assets/lorem_ipsum.txt"),
+        png.decrypt_into(&mut buffer),
+        include_bytes!("../assets/boo.png")
+    );
+    let mut buffer = [0; 26];
+    assert_eq!(
+        boo_include_bytes!(manifest = "assets/lorem_ipsum.txt", compress).decrypt_into(&mut buffer),
+        b"Lorem ipsum dolor sit amet"
+    );
+    assert_eq!(
+        boo_include_str!("../assets/lorem_ipsum.txt", compress).decrypt_into(&mut buffer),
         "Lorem ipsum dolor sit amet"
     );
+    let source = boo_include_str!("test.rs", compress);
+    let mut buffer = alloc::vec![0; source.len()];
+    assert_eq!(source.decrypt_into(&mut buffer), include_str!("test.rs"));
+}
+
+#[test]
+fn test_stream_iterator() {
+    let stream = boo_stream!("../assets/boo.png");
+
+    assert_eq!(stream.len(), include_bytes!("../assets/boo.png").len());
+    assert!(stream.eq(include_bytes!("../assets/boo.png").iter().copied()));
+}
+
+#[test]
+fn test_stream_chunks() {
+    let mut stream = boo_stream!("../assets/boo.png");
+    let mut bytes = alloc::vec::Vec::new();
+    while let Some(chunk) = stream.next_chunk() {
+        bytes.extend_from_slice(chunk);
+    }
+
+    assert_eq!(bytes, include_bytes!("../assets/boo.png"));
+}
+
+#[test]
+fn test_stream_read() {
+    let mut stream = boo_stream!("../assets/lorem_ipsum.txt");
+    let mut buf = [0; 5];
+
+    assert_eq!(stream.read(&mut buf), 5);
+    assert_eq!(&buf, b"Lorem");
+    assert_eq!(stream.next(), Some(b' '));
+    assert_eq!(stream.next_chunk(), Some(&b"ipsum dolor sit amet"[..]));
+    assert_eq!(stream.read(&mut buf), 0);
+    assert_eq!(stream.next(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_stream_io_read() {
+    use std::io::Read;
+
+    let mut bytes = std::vec::Vec::new();
+    boo_stream!("../assets/boo.png")
+        .read_to_end(&mut bytes)
+        .unwrap();
+
+    assert_eq!(bytes, include_bytes!("../assets/boo.png"));
+}
+
+#[test]
+fn test_include_dir() {
+    let dir = boo_include_dir!("../assets");
+    let names = dir
+        .iter()
+        .map(|file| file.name())
+        .collect::<alloc::vec::Vec<_>>();
+
+    assert_eq!(names, [Some("boo.png"), Some("lorem_ipsum.txt")]);
+    assert!(
+        dir.get("boo.png")
+            .unwrap()
+            .eq(include_bytes!("../assets/boo.png").iter().copied())
+    );
+    assert!(dir.get("missing.txt").is_none());
+
+    let mut buf = [0; 26];
+    assert_eq!(dir.get("lorem_ipsum.txt").unwrap().read(&mut buf), 26);
+    assert_eq!(&buf, b"Lorem ipsum dolor sit amet");
+}
+
+#[test]
+fn test_include_dir_encrypted_names() {
+    let dir = boo_include_dir!(manifest = "assets/", encrypt_names);
+
+    assert_eq!(dir.len(), 2);
+    assert!(dir.iter().all(|file| file.name().is_none()));
+    assert!(dir.file("boo.png").unwrap().is("boo.png"));
+    assert_eq!(dir.file("lorem_ipsum.txt").unwrap().len(), 26);
+    assert!(dir.get("lorem_ipsum").is_none());
+    assert!(dir.get("lorem_ipsum.txt.bak").is_none());
+}
+
+#[test]
+fn test_static() {
+    let url: &str = &API_URL;
+    assert_eq!(url, "https://example.com");
+    assert!(API_URL.is_decrypted());
+    assert_eq!(*PORT, 443);
+
+    let [(a, b), (c, d)] = &*HEADERS;
+    assert_eq!((a.as_str(), b.as_c_str()), ("a", c"b"));
+    assert_eq!((c.as_str(), d.as_c_str()), ("cd", c"e"));
+}
+
+#[test]
+fn test_static_reset() {
+    boo_static!(
+        static SECRET: &[u8; 3] = b"abc";
+    );
+
+    assert!(!SECRET.is_decrypted());
+    assert_eq!(&*SECRET, b"abc");
+    unsafe { SECRET.reset() };
+    assert!(!SECRET.is_decrypted());
+    assert_eq!(&*SECRET, b"abc");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_static_decrypt_panic() {
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    static PANICKED: AtomicBool = AtomicBool::new(false);
+    static SECRET: __boo::BooStatic<u8> = __boo::BooStatic::new(|| {
+        if !PANICKED.swap(true, Ordering::SeqCst) {
+            panic!("decryption failed");
+        }
+        boo!(7u8)
+    });
+
+    assert!(std::panic::catch_unwind(|| *SECRET).is_err());
+    assert!(!SECRET.is_decrypted());
+    assert_eq!(*SECRET, 7);
+}
+
+#[boo::encrypt_literals]
+fn encrypted_greeting(name: &str) -> alloc::string::String {
+    const PREFIX: &str = "Hello";
+    alloc::format!("{PREFIX} {}{}", name, "!")
+}
+
+#[boo::encrypt_literals]
+fn encrypted_literals(value: u8) -> (usize, u8, i64, f32, bool) {
+    let str: &str = "secret";
+    let bytes: &[u8; 3] = b"abc";
+    let cstr: &core::ffi::CStr = c"c";
+    let array = [0u8; 4];
+    let matched = match value {
+        1 => true,
+        2 => false,
+        _ => value > 200,
+    };
+
+    (
+        str.len() + bytes.len() + cstr.to_bytes().len() + array.len(),
+        value + 1,
+        -9_000_000_000,
+        0.5 * 2.0,
+        matched,
+    )
+}
+
+macro_rules! join {
+    ($($part:expr),*) => { [$($part),*].concat() };
+}
+
+// Forwards to a format string, like the `log` macros called by their name
+macro_rules! message {
+    ($($arg:tt)*) => { alloc::format!($($arg)*) };
+}
+
+#[boo::encrypt_literals]
+fn encrypted_messages() -> (alloc::string::String, alloc::string::String) {
+    (message!("Connecting"), message!("Port {}", 443))
+}
+
+#[boo::encrypt_literals]
+fn encrypted_macros() -> (alloc::string::String, alloc::vec::Vec<u8>, bool) {
+    (
+        join!("se", "cret"),
+        alloc::vec![7u8; 2],
+        matches!(Some(1), Some(1)),
+    )
+}
+
+#[boo::encrypt_literals]
+fn encrypted_borrows() -> (&'static str, usize, &'static [u8; 2]) {
+    let names = alloc::vec!["a", "b"];
+    let mut map = alloc::collections::BTreeMap::new();
+    map.insert("k", 1);
+
+    ("static", names.len() + map["k"], b"ab")
+}
+
+struct Encrypted;
+
+#[boo::encrypt_literals]
+impl Encrypted {
+    fn describe(&self) -> alloc::string::String {
+        alloc::string::String::from("encrypted")
+    }
+}
+
+#[boo::encrypt_literals]
+mod encrypted_module {
+    pub fn answer() -> u64 {
+        40 + 2
+    }
+
+    pub trait Limit<const MAX: u64 = 100> {
+        const MIN: u64 = 1;
+
+        fn range() -> (u64, u64) {
+            (Self::MIN, MAX)
+        }
+    }
+}
+
+impl encrypted_module::Limit for Encrypted {}
+
+#[test]
+fn test_encrypt_literals() {
+    assert_eq!(encrypted_greeting("boo"), "Hello boo!");
+    assert_eq!(encrypted_literals(1), (14, 2, -9_000_000_000, 1.0, true));
+    assert!(!encrypted_literals(2).4);
+    assert_eq!(Encrypted.describe(), "encrypted");
+    assert_eq!(encrypted_module::answer(), 42);
+    assert_eq!(encrypted_borrows(), ("static", 3, b"ab"));
+    assert_eq!(<Encrypted as encrypted_module::Limit>::range(), (1, 100));
+    assert_eq!(
+        encrypted_macros(),
+        ("secret".to_owned(), alloc::vec![7, 7], true)
+    );
+    assert_eq!(
+        encrypted_messages(),
+        ("Connecting".to_owned(), "Port 443".to_owned())
+    );
+}
+
+#[test]
+fn test_format() {
+    use alloc::format;
+
+    let token = "abc";
+    let width = 6;
+    assert_eq!(
+        boo_format!("Connecting to {} with token {token}", "host"),
+        "Connecting to host with token abc"
+    );
+    assert_eq!(boo_format!("{1}-{0}-{1}", 'a', 'b'), "b-a-b");
+    assert_eq!(boo_format!("{{{value:?}}}", value = "x"), "{\"x\"}");
+    assert_eq!(boo_format!("[{:>width$}] [{:<1$}]", 1, 3), "[     1] [3  ]");
+    assert_eq!(boo_format!("{}", 1.5), "1.5");
+    assert_eq!(boo_format!("no placeholders"), "no placeholders");
+}
+
+#[test]
+fn test_write() {
+    use core::fmt::Write;
+
+    let mut buffer = alloc::string::String::new();
+    boo_write!(buffer, "{} + {} = ", 1, 2).unwrap();
+    boo_writeln!(&mut buffer, "{}", 3).unwrap();
+
+    assert_eq!(buffer, "1 + 2 = 3\n");
+}
+
+#[test]
+#[should_panic(expected = "secret failure 42")]
+fn test_panic() {
+    boo_panic!("secret failure {}", 42);
 }